@@ -0,0 +1,421 @@
+//! Exact integer amount types for SOL and SPL tokens.
+//!
+//! Amounts are kept as integers of the smallest unit and only converted to
+//! the decimal form FURY API expects when serialized.
+
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+
+const SOL_DECIMALS: u8 = 9;
+
+/// Most decimals a `u64` amount can have, as `10^19` is the largest power
+/// of ten that fits
+pub const MAX_DECIMALS: u8 = 19;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AmountError {
+    /// The string is not a decimal number
+    InvalidFormat(String),
+    /// More fractional digits than the unit supports
+    TooManyDecimals { max: u8 },
+    /// The unit suffix is not recognised
+    UnknownUnit(String),
+    /// The amount does not fit into a `u64`
+    Overflow,
+}
+
+impl Display for AmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AmountError::InvalidFormat(s) => write!(f, "Invalid amount: {}", s),
+            AmountError::TooManyDecimals { max } => {
+                write!(f, "Amount has more than {} decimal places", max)
+            }
+            AmountError::UnknownUnit(unit) => write!(f, "Unknown amount unit: {}", unit),
+            AmountError::Overflow => write!(f, "Amount overflows u64"),
+        }
+    }
+}
+
+impl std::error::Error for AmountError {}
+
+/// An amount of SOL, stored in lamports.
+///
+/// Parses from `"0.001 SOL"`, `"1000000 lamports"` or a bare SOL value such as
+/// `"0.001"`. Serialized as a SOL-denominated JSON number, which is the form
+/// FURY API uses for SOL amounts. The number goes through `f64`, so it is
+/// exact up to 15 significant digits, i.e. below 1,000,000 SOL.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Lamports(pub u64);
+
+impl Lamports {
+    pub const ZERO: Lamports = Lamports(0);
+
+    pub const fn new(lamports: u64) -> Self {
+        Lamports(lamports)
+    }
+
+    /// Whole SOL amount, e.g. `Lamports::sol(2)` for 2 SOL
+    pub fn sol(sol: u64) -> Option<Self> {
+        sol.checked_mul(LAMPORTS_PER_SOL).map(Lamports)
+    }
+
+    /// Parses a decimal SOL value such as `"0.001"` exactly
+    pub fn from_sol_str(sol: &str) -> Result<Self, AmountError> {
+        parse_decimal(sol, SOL_DECIMALS).map(Lamports)
+    }
+
+    pub const fn lamports(self) -> u64 {
+        self.0
+    }
+
+    /// Exact decimal SOL representation, e.g. `"0.001"`
+    pub fn to_sol_string(self) -> String {
+        format_decimal(self.0, SOL_DECIMALS)
+    }
+
+    pub fn checked_add(self, other: Lamports) -> Option<Lamports> {
+        self.0.checked_add(other.0).map(Lamports)
+    }
+
+    pub fn checked_sub(self, other: Lamports) -> Option<Lamports> {
+        self.0.checked_sub(other.0).map(Lamports)
+    }
+
+    pub fn checked_mul(self, factor: u64) -> Option<Lamports> {
+        self.0.checked_mul(factor).map(Lamports)
+    }
+
+    pub fn checked_div(self, divisor: u64) -> Option<Lamports> {
+        self.0.checked_div(divisor).map(Lamports)
+    }
+
+    pub fn saturating_sub(self, other: Lamports) -> Lamports {
+        Lamports(self.0.saturating_sub(other.0))
+    }
+}
+
+impl From<u64> for Lamports {
+    fn from(lamports: u64) -> Self {
+        Lamports(lamports)
+    }
+}
+
+impl From<Lamports> for u64 {
+    fn from(lamports: Lamports) -> Self {
+        lamports.0
+    }
+}
+
+impl Display for Lamports {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} SOL", self.to_sol_string())
+    }
+}
+
+impl FromStr for Lamports {
+    type Err = AmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (value, unit) = match s.split_once(char::is_whitespace) {
+            Some((value, unit)) => (value, unit.trim()),
+            None => (s, "sol"),
+        };
+
+        match unit.to_ascii_lowercase().as_str() {
+            "sol" => Lamports::from_sol_str(value),
+            "lamport" | "lamports" => parse_decimal(value, 0).map(Lamports),
+            _ => Err(AmountError::UnknownUnit(unit.to_string())),
+        }
+    }
+}
+
+impl Serialize for Lamports {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_decimal_number(&self.to_sol_string(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Lamports {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let sol = deserialize_decimal_string(deserializer, SOL_DECIMALS)?;
+        Lamports::from_sol_str(&sol).map_err(de::Error::custom)
+    }
+}
+
+/// An SPL token amount in raw base units together with the mint's decimals.
+///
+/// Serialized as an exact decimal string in UI units, e.g. `"1.5"`. Amounts
+/// decoded from transactions can carry more than `MAX_DECIMALS` decimals,
+/// as the fields are public; they still format exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TokenAmount {
+    pub raw: u64,
+    pub decimals: u8,
+}
+
+impl TokenAmount {
+    /// Fails if `decimals` exceeds `MAX_DECIMALS`
+    pub fn new(raw: u64, decimals: u8) -> Result<Self, AmountError> {
+        if decimals > MAX_DECIMALS {
+            return Err(AmountError::TooManyDecimals { max: MAX_DECIMALS });
+        }
+        Ok(TokenAmount { raw, decimals })
+    }
+
+    /// Parses a UI amount such as `"1.5"` for a mint with `decimals` decimals
+    pub fn from_ui_str(ui_amount: &str, decimals: u8) -> Result<Self, AmountError> {
+        parse_decimal(ui_amount, decimals).map(|raw| TokenAmount { raw, decimals })
+    }
+
+    /// Exact decimal UI representation, e.g. `"1.5"`
+    pub fn to_ui_string(self) -> String {
+        format_decimal(self.raw, self.decimals)
+    }
+
+    /// Adds two amounts of the same mint. Returns `None` on overflow or if
+    /// the decimals differ.
+    pub fn checked_add(self, other: TokenAmount) -> Option<TokenAmount> {
+        if self.decimals != other.decimals {
+            return None;
+        }
+        self.raw
+            .checked_add(other.raw)
+            .map(|raw| TokenAmount { raw, ..self })
+    }
+
+    /// Subtracts two amounts of the same mint. Returns `None` on underflow or
+    /// if the decimals differ.
+    pub fn checked_sub(self, other: TokenAmount) -> Option<TokenAmount> {
+        if self.decimals != other.decimals {
+            return None;
+        }
+        self.raw
+            .checked_sub(other.raw)
+            .map(|raw| TokenAmount { raw, ..self })
+    }
+
+    pub fn checked_mul(self, factor: u64) -> Option<TokenAmount> {
+        self.raw
+            .checked_mul(factor)
+            .map(|raw| TokenAmount { raw, ..self })
+    }
+
+    pub fn checked_div(self, divisor: u64) -> Option<TokenAmount> {
+        self.raw
+            .checked_div(divisor)
+            .map(|raw| TokenAmount { raw, ..self })
+    }
+}
+
+impl Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_ui_string())
+    }
+}
+
+impl Serialize for TokenAmount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_ui_string())
+    }
+}
+
+/// Serializes a `TokenAmount` as a JSON number in UI units, for endpoints
+/// that take a numeric token amount
+pub fn serialize_token_ui_number<S: Serializer>(
+    amount: &TokenAmount,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serialize_decimal_number(&amount.to_ui_string(), serializer)
+}
+
+fn serialize_decimal_number<S: Serializer>(
+    decimal: &str,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    // The shortest round-trip representation of the nearest f64 is the
+    // decimal itself for any value with up to 15 significant digits
+    let value: f64 = decimal.parse().map_err(serde::ser::Error::custom)?;
+    serializer.serialize_f64(value)
+}
+
+/// Reads a JSON number or string and returns its decimal text. Floats are
+/// rounded to `decimals` places, dropping the noise of their binary form
+/// such as the trailing digits of `0.30000000000000004`.
+fn deserialize_decimal_string<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    decimals: u8,
+) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum NumberOrString {
+        Integer(u64),
+        Float(f64),
+        String(String),
+    }
+
+    Ok(match NumberOrString::deserialize(deserializer)? {
+        NumberOrString::Integer(value) => value.to_string(),
+        NumberOrString::Float(value) => format!("{:.*}", decimals as usize, value),
+        NumberOrString::String(value) => value,
+    })
+}
+
+/// Parses a non-negative decimal string into an integer of `decimals`
+/// fractional digits without going through floating point
+fn parse_decimal(s: &str, decimals: u8) -> Result<u64, AmountError> {
+    let s = s.trim();
+    let invalid = || AmountError::InvalidFormat(s.to_string());
+
+    let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+    if (whole.is_empty() && fraction.is_empty())
+        || !whole.bytes().all(|b| b.is_ascii_digit())
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimals as usize {
+        return Err(AmountError::TooManyDecimals { max: decimals });
+    }
+
+    let scale = 10u64
+        .checked_pow(decimals as u32)
+        .ok_or(AmountError::Overflow)?;
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().map_err(|_| AmountError::Overflow)?
+    };
+    let fraction: u64 = if fraction.is_empty() {
+        0
+    } else {
+        let padding = 10u64.pow((decimals as usize - fraction.len()) as u32);
+        fraction.parse::<u64>().map_err(|_| invalid())? * padding
+    };
+
+    whole
+        .checked_mul(scale)
+        .and_then(|value| value.checked_add(fraction))
+        .ok_or(AmountError::Overflow)
+}
+
+fn format_decimal(value: u64, decimals: u8) -> String {
+    if decimals == 0 {
+        return value.to_string();
+    }
+
+    // Out of range decimals can only come from a hand-built or decoded
+    // `TokenAmount`, print them without scaling
+    let Some(scale) = 10u128.checked_pow(decimals as u32) else {
+        return format!("{}e-{}", value, decimals);
+    };
+    let whole = value as u128 / scale;
+    let fraction = value as u128 % scale;
+    if fraction == 0 {
+        return whole.to_string();
+    }
+
+    let fraction = format!("{:0width$}", fraction, width = decimals as usize);
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_out_of_range_decimals() {
+        assert_eq!(
+            TokenAmount {
+                raw: 15,
+                decimals: 20
+            }
+            .to_string(),
+            "0.00000000000000000015"
+        );
+        assert_eq!(
+            TokenAmount {
+                raw: 15,
+                decimals: 255
+            }
+            .to_string(),
+            "15e-255"
+        );
+        assert_eq!(
+            serde_json::to_string(&TokenAmount {
+                raw: 1,
+                decimals: 40
+            })
+            .unwrap(),
+            "\"1e-40\""
+        );
+    }
+
+    #[test]
+    fn new_rejects_too_many_decimals() {
+        assert_eq!(
+            TokenAmount::new(1, 19).unwrap().to_string(),
+            "0.0000000000000000001"
+        );
+        assert_eq!(
+            TokenAmount::new(1, 20),
+            Err(AmountError::TooManyDecimals { max: MAX_DECIMALS })
+        );
+    }
+
+    #[test]
+    fn parses_and_formats_exactly() {
+        let amount = TokenAmount::from_ui_str("1.5", 6).unwrap();
+        assert_eq!(amount.raw, 1_500_000);
+        assert_eq!(amount.to_string(), "1.5");
+        assert_eq!(
+            Lamports::from_sol_str("0.001").unwrap(),
+            Lamports(1_000_000)
+        );
+        assert_eq!(
+            "0.001 SOL".parse::<Lamports>().unwrap().to_string(),
+            "0.001 SOL"
+        );
+    }
+
+    #[test]
+    fn deserializes_float_noise() {
+        for (json, lamports) in [
+            ("0.30000000000000004", 300_000_000),
+            ("0.1", 100_000_000),
+            ("1e-9", 1),
+            ("123456.789", 123_456_789_000_000),
+            ("5", 5_000_000_000),
+            ("\"0.000000001\"", 1),
+        ] {
+            assert_eq!(
+                serde_json::from_str::<Lamports>(json).unwrap(),
+                Lamports(lamports),
+                "{}",
+                json
+            );
+        }
+        assert!(serde_json::from_str::<Lamports>("\"0.0000000001\"").is_err());
+        assert!(serde_json::from_str::<Lamports>("-0.5").is_err());
+        assert!(serde_json::from_str::<Lamports>("1e30").is_err());
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        for lamports in [0, 1, 300_000_000, 999_999_999_999_999] {
+            let json = serde_json::to_string(&Lamports(lamports)).unwrap();
+            assert_eq!(
+                serde_json::from_str::<Lamports>(&json).unwrap(),
+                Lamports(lamports)
+            );
+        }
+    }
+}
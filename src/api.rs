@@ -0,0 +1,273 @@
+//! `FuryApi`, the endpoints of FURY API as a trait, so code built on the SDK
+//! can run against `fake::FakeFuryApi` in tests.
+
+use std::{
+    fmt::{self, Display},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use solana_sdk::{pubkey::Pubkey, transaction::VersionedTransaction};
+
+use crate::sdk::{
+    AnalyticsPnlOptions, AnalyticsPnlResponse, BuyTokenRequest, BuyTokenResponse, Delivery,
+    FuryError, FurySDK, GenerateMintResponse, HealthCheckResponse, SellRequest, SellResponse,
+    SendOutcome, TokenBurnRequest, TokenBurnResponse, TokenCleanerRequest, TokenCleanerResponse,
+    TokenTransferRequest, TokenTransferResponse, TokensCreateRequest, TokensCreateResponse,
+    WalletsConsolidateRequest, WalletsConsolidateResponse, WalletsDistributeRequest,
+    WalletsDistributeResponse,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    HealthCheck,
+    BuyToken,
+    SellToken,
+    SendTransactions,
+    TokenTransfer,
+    TokensCreate,
+    TokenBurn,
+    TokenCleaner,
+    AnalyticsPnl,
+    GenerateMint,
+    WalletsDistribute,
+    WalletsConsolidate,
+}
+
+impl Endpoint {
+    /// Path relative to the API root
+    pub fn path(&self) -> &'static str {
+        match self {
+            Endpoint::HealthCheck => "health",
+            Endpoint::BuyToken => "tokens/buy",
+            Endpoint::SellToken => "tokens/sell",
+            Endpoint::SendTransactions => "transactions/send",
+            Endpoint::TokenTransfer => "tokens/transfer",
+            Endpoint::TokensCreate => "tokens/create",
+            Endpoint::TokenBurn => "tokens/burn",
+            Endpoint::TokenCleaner => "tokens/cleaner",
+            Endpoint::AnalyticsPnl => "analytics/pnl",
+            Endpoint::GenerateMint => "utilities/generate-mint",
+            Endpoint::WalletsDistribute => "wallets/distribute",
+            Endpoint::WalletsConsolidate => "wallets/consolidate",
+        }
+    }
+}
+
+impl Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path())
+    }
+}
+
+/// Every FURY API endpoint. Implemented by `FurySDK` and
+/// `fake::FakeFuryApi`.
+///
+/// `FurySDK`'s inherent methods take precedence, so bringing this trait
+/// into scope does not change existing calls.
+#[async_trait]
+pub trait FuryApi: Send + Sync {
+    async fn health_check(&self) -> Result<HealthCheckResponse, FuryError>;
+
+    async fn buy_token(&self, data: &BuyTokenRequest) -> Result<BuyTokenResponse, FuryError>;
+
+    async fn sell_token(&self, data: &SellRequest) -> Result<SellResponse, FuryError>;
+
+    /// Submits signed transactions. `FurySDK::send_transactions` also
+    /// accepts base58 strings.
+    async fn send_transactions(
+        &self,
+        transactions: &[VersionedTransaction],
+        delivery: Delivery,
+    ) -> Result<SendOutcome, FuryError>;
+
+    async fn token_transfer(
+        &self,
+        data: &TokenTransferRequest,
+    ) -> Result<TokenTransferResponse, FuryError>;
+
+    async fn tokens_create(
+        &self,
+        data: &TokensCreateRequest,
+    ) -> Result<TokensCreateResponse, FuryError>;
+
+    async fn token_burn(&self, data: &TokenBurnRequest) -> Result<TokenBurnResponse, FuryError>;
+
+    async fn token_cleaner(
+        &self,
+        data: &TokenCleanerRequest,
+    ) -> Result<TokenCleanerResponse, FuryError>;
+
+    async fn analytics_pnl(
+        &self,
+        addresses: &[Pubkey],
+        token_address: Pubkey,
+        options: AnalyticsPnlOptions,
+    ) -> Result<AnalyticsPnlResponse, FuryError>;
+
+    async fn generate_mint(&self) -> Result<GenerateMintResponse, FuryError>;
+
+    async fn wallets_distribute(
+        &self,
+        data: &WalletsDistributeRequest,
+    ) -> Result<WalletsDistributeResponse, FuryError>;
+
+    async fn wallets_consolidate(
+        &self,
+        data: &WalletsConsolidateRequest,
+    ) -> Result<WalletsConsolidateResponse, FuryError>;
+}
+
+#[async_trait]
+impl FuryApi for FurySDK {
+    async fn health_check(&self) -> Result<HealthCheckResponse, FuryError> {
+        FurySDK::health_check(self).await
+    }
+
+    async fn buy_token(&self, data: &BuyTokenRequest) -> Result<BuyTokenResponse, FuryError> {
+        FurySDK::buy_token(self, data).await
+    }
+
+    async fn sell_token(&self, data: &SellRequest) -> Result<SellResponse, FuryError> {
+        FurySDK::sell_token(self, data).await
+    }
+
+    async fn send_transactions(
+        &self,
+        transactions: &[VersionedTransaction],
+        delivery: Delivery,
+    ) -> Result<SendOutcome, FuryError> {
+        FurySDK::send_transactions(self, transactions, delivery).await
+    }
+
+    async fn token_transfer(
+        &self,
+        data: &TokenTransferRequest,
+    ) -> Result<TokenTransferResponse, FuryError> {
+        FurySDK::token_transfer(self, data).await
+    }
+
+    async fn tokens_create(
+        &self,
+        data: &TokensCreateRequest,
+    ) -> Result<TokensCreateResponse, FuryError> {
+        FurySDK::tokens_create(self, data).await
+    }
+
+    async fn token_burn(&self, data: &TokenBurnRequest) -> Result<TokenBurnResponse, FuryError> {
+        FurySDK::token_burn(self, data).await
+    }
+
+    async fn token_cleaner(
+        &self,
+        data: &TokenCleanerRequest,
+    ) -> Result<TokenCleanerResponse, FuryError> {
+        FurySDK::token_cleaner(self, data).await
+    }
+
+    async fn analytics_pnl(
+        &self,
+        addresses: &[Pubkey],
+        token_address: Pubkey,
+        options: AnalyticsPnlOptions,
+    ) -> Result<AnalyticsPnlResponse, FuryError> {
+        FurySDK::analytics_pnl(self, addresses, token_address, options).await
+    }
+
+    async fn generate_mint(&self) -> Result<GenerateMintResponse, FuryError> {
+        FurySDK::generate_mint(self).await
+    }
+
+    async fn wallets_distribute(
+        &self,
+        data: &WalletsDistributeRequest,
+    ) -> Result<WalletsDistributeResponse, FuryError> {
+        FurySDK::wallets_distribute(self, data).await
+    }
+
+    async fn wallets_consolidate(
+        &self,
+        data: &WalletsConsolidateRequest,
+    ) -> Result<WalletsConsolidateResponse, FuryError> {
+        FurySDK::wallets_consolidate(self, data).await
+    }
+}
+
+#[async_trait]
+impl<T: FuryApi + ?Sized> FuryApi for Arc<T> {
+    async fn health_check(&self) -> Result<HealthCheckResponse, FuryError> {
+        self.as_ref().health_check().await
+    }
+
+    async fn buy_token(&self, data: &BuyTokenRequest) -> Result<BuyTokenResponse, FuryError> {
+        self.as_ref().buy_token(data).await
+    }
+
+    async fn sell_token(&self, data: &SellRequest) -> Result<SellResponse, FuryError> {
+        self.as_ref().sell_token(data).await
+    }
+
+    async fn send_transactions(
+        &self,
+        transactions: &[VersionedTransaction],
+        delivery: Delivery,
+    ) -> Result<SendOutcome, FuryError> {
+        self.as_ref()
+            .send_transactions(transactions, delivery)
+            .await
+    }
+
+    async fn token_transfer(
+        &self,
+        data: &TokenTransferRequest,
+    ) -> Result<TokenTransferResponse, FuryError> {
+        self.as_ref().token_transfer(data).await
+    }
+
+    async fn tokens_create(
+        &self,
+        data: &TokensCreateRequest,
+    ) -> Result<TokensCreateResponse, FuryError> {
+        self.as_ref().tokens_create(data).await
+    }
+
+    async fn token_burn(&self, data: &TokenBurnRequest) -> Result<TokenBurnResponse, FuryError> {
+        self.as_ref().token_burn(data).await
+    }
+
+    async fn token_cleaner(
+        &self,
+        data: &TokenCleanerRequest,
+    ) -> Result<TokenCleanerResponse, FuryError> {
+        self.as_ref().token_cleaner(data).await
+    }
+
+    async fn analytics_pnl(
+        &self,
+        addresses: &[Pubkey],
+        token_address: Pubkey,
+        options: AnalyticsPnlOptions,
+    ) -> Result<AnalyticsPnlResponse, FuryError> {
+        self.as_ref()
+            .analytics_pnl(addresses, token_address, options)
+            .await
+    }
+
+    async fn generate_mint(&self) -> Result<GenerateMintResponse, FuryError> {
+        self.as_ref().generate_mint().await
+    }
+
+    async fn wallets_distribute(
+        &self,
+        data: &WalletsDistributeRequest,
+    ) -> Result<WalletsDistributeResponse, FuryError> {
+        self.as_ref().wallets_distribute(data).await
+    }
+
+    async fn wallets_consolidate(
+        &self,
+        data: &WalletsConsolidateRequest,
+    ) -> Result<WalletsConsolidateResponse, FuryError> {
+        self.as_ref().wallets_consolidate(data).await
+    }
+}
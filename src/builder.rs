@@ -0,0 +1,352 @@
+use std::{sync::Arc, time::Duration};
+
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, USER_AGENT},
+    Client, Proxy, Url,
+};
+
+#[cfg(feature = "tracing")]
+use crate::trace::AddressLogging;
+use crate::{
+    cassette::Cassette,
+    retry::RetryPolicy,
+    sdk::{FuryError, FurySDK, DEFAULT_BASE_URL},
+};
+
+/// Header used to send the FURY API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+const DEFAULT_USER_AGENT: &str = concat!("fury_sdk/", env!("CARGO_PKG_VERSION"));
+
+/// Builds a `FurySDK` together with its underlying `reqwest::Client`.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use fury_sdk::sdk::FurySDK;
+/// let fury = FurySDK::builder()
+///     .api_key("my-api-key")
+///     .timeout(Duration::from_secs(10))
+///     .build()?;
+/// # Ok::<(), fury_sdk::sdk::FuryError>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct FurySDKBuilder {
+    base_url: Option<String>,
+    health_url: Option<String>,
+    api_key: Option<String>,
+    bearer_token: Option<String>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    user_agent: Option<String>,
+    headers: Vec<(String, String)>,
+    proxy: Option<Proxy>,
+    retry_policy: Option<RetryPolicy>,
+    cassette: Option<Arc<Cassette>>,
+    #[cfg(feature = "tracing")]
+    address_logging: Option<AddressLogging>,
+}
+
+impl FurySDKBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// API root, e.g. `https://solana.fury.bot/api/`. A missing trailing
+    /// slash is added automatically.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Root of the health endpoint. Defaults to the parent of the API root
+    /// when it ends in `api/`, otherwise to the API root itself.
+    pub fn health_url(mut self, health_url: impl Into<String>) -> Self {
+        self.health_url = Some(health_url.into());
+        self
+    }
+
+    /// API key sent in the `x-api-key` header of every request
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Token sent as `Authorization: Bearer <token>` on every request
+    pub fn bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.bearer_token = Some(token.into());
+        self
+    }
+
+    /// Total time allowed for a single request attempt
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Time allowed to establish a connection
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Defaults to `fury_sdk/<version>`. Cannot be combined with a
+    /// `user-agent` passed to `header`.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Extra header sent on every request. Invalid names or values are
+    /// reported by `build`.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Records requests to, or replays them from, a cassette file
+    pub fn cassette(mut self, cassette: impl Into<Arc<Cassette>>) -> Self {
+        self.cassette = Some(cassette.into());
+        self
+    }
+
+    /// How wallet addresses appear in tracing spans. Defaults to
+    /// `AddressLogging::Abbreviated`.
+    #[cfg(feature = "tracing")]
+    pub fn address_logging(mut self, address_logging: AddressLogging) -> Self {
+        self.address_logging = Some(address_logging);
+        self
+    }
+
+    pub fn build(self) -> Result<FurySDK, FuryError> {
+        let base_url = normalize_base_url(self.base_url.as_deref().unwrap_or(DEFAULT_BASE_URL))?;
+        let health_url = match self.health_url.as_deref() {
+            Some(health_url) => normalize_base_url(health_url)?,
+            None => default_health_url(&base_url),
+        };
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| FuryError::ConfigError(format!("Invalid header name: {}", name)))?;
+            let value = HeaderValue::from_str(value).map_err(|_| {
+                FuryError::ConfigError(format!("Invalid value for header {}", name))
+            })?;
+            headers.insert(name, value);
+        }
+        if let Some(api_key) = &self.api_key {
+            headers.insert(API_KEY_HEADER, sensitive_header(api_key, "API key")?);
+        }
+        if let Some(token) = &self.bearer_token {
+            let value = sensitive_header(&format!("Bearer {}", token), "bearer token")?;
+            headers.insert(AUTHORIZATION, value);
+        }
+        // A user agent passed to `header` is kept, unless it clashes with
+        // `user_agent`
+        match (&self.user_agent, headers.contains_key(USER_AGENT)) {
+            (Some(_), true) => {
+                return Err(FuryError::ConfigError(
+                    "User agent set both by user_agent and header".to_string(),
+                ))
+            }
+            (_, true) => {}
+            (user_agent, false) => {
+                let user_agent = user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT);
+                let user_agent = HeaderValue::from_str(user_agent)
+                    .map_err(|_| FuryError::ConfigError("Invalid user agent".to_string()))?;
+                headers.insert(USER_AGENT, user_agent);
+            }
+        }
+
+        let mut client = Client::builder().default_headers(headers);
+        if let Some(timeout) = self.timeout {
+            client = client.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            client = client.connect_timeout(timeout);
+        }
+        if let Some(proxy) = self.proxy {
+            client = client.proxy(proxy);
+        }
+        let client = client.build().map_err(FuryError::RequestError)?;
+
+        Ok(FurySDK {
+            client,
+            base_url,
+            health_url,
+            retry_policy: self.retry_policy.unwrap_or_else(RetryPolicy::none),
+            cassette: self.cassette,
+            #[cfg(feature = "tracing")]
+            address_logging: self.address_logging.unwrap_or_default(),
+        })
+    }
+}
+
+fn sensitive_header(value: &str, what: &str) -> Result<HeaderValue, FuryError> {
+    let mut value = HeaderValue::from_str(value)
+        .map_err(|_| FuryError::ConfigError(format!("Invalid {}", what)))?;
+    value.set_sensitive(true);
+    Ok(value)
+}
+
+/// Checks that `base_url` is an absolute http(s) URL and ensures its path
+/// ends with a slash so endpoints can be joined onto it.
+pub(crate) fn normalize_base_url(base_url: &str) -> Result<Url, FuryError> {
+    let mut url = Url::parse(base_url)
+        .map_err(|e| FuryError::ConfigError(format!("Invalid base URL {}: {}", base_url, e)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(FuryError::ConfigError(format!(
+            "Base URL must use http or https: {}",
+            base_url
+        )));
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(FuryError::ConfigError(format!(
+            "Base URL must not contain a query or fragment: {}",
+            base_url
+        )));
+    }
+
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+    Ok(url)
+}
+
+/// Health root derived from the API root: `https://host/api/` maps to
+/// `https://host/`, any other root is used as is.
+pub(crate) fn default_health_url(base_url: &Url) -> Url {
+    let is_api_root = base_url
+        .path_segments()
+        .and_then(|mut segments| segments.rfind(|s| !s.is_empty()))
+        == Some("api");
+    if is_api_root {
+        base_url.join("../").unwrap_or_else(|_| base_url.clone())
+    } else {
+        base_url.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// `User-Agent` the SDK sends on a health check
+    async fn sent_user_agent(builder: FurySDKBuilder) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api/", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 4096];
+            let read = stream.read(&mut request).await.unwrap();
+            let body = r#"{"status":"ok","timestamp":"now"}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request[..read]).to_ascii_lowercase()
+        });
+
+        let fury = builder.base_url(url).build().unwrap();
+        let _ = fury.health_check().await;
+        let request = server.await.unwrap();
+        request
+            .lines()
+            .find_map(|line| line.strip_prefix("user-agent: "))
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    #[test]
+    fn normalizes_base_url() {
+        for (base_url, normalized) in [
+            ("https://example.com", "https://example.com/"),
+            ("https://example.com/api", "https://example.com/api/"),
+            ("https://example.com/api/", "https://example.com/api/"),
+            (
+                "http://localhost:8080/v1/fury",
+                "http://localhost:8080/v1/fury/",
+            ),
+        ] {
+            assert_eq!(normalize_base_url(base_url).unwrap().as_str(), normalized);
+        }
+        let joined = normalize_base_url("https://example.com/v1/fury").unwrap();
+        assert_eq!(
+            joined.join("tokens/buy").unwrap().as_str(),
+            "https://example.com/v1/fury/tokens/buy"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_base_url() {
+        for base_url in [
+            "example.com/api",
+            "ftp://example.com/api/",
+            "https://example.com/api/?key=secret",
+            "https://example.com/api/#section",
+        ] {
+            assert!(
+                matches!(normalize_base_url(base_url), Err(FuryError::ConfigError(_))),
+                "{} accepted",
+                base_url
+            );
+        }
+    }
+
+    #[test]
+    fn derives_health_url_from_api_root() {
+        for (base_url, health_url) in [
+            ("https://example.com/api/", "https://example.com/"),
+            ("https://example.com/v2/api", "https://example.com/v2/"),
+            ("https://example.com/", "https://example.com/"),
+            ("https://example.com/v1/", "https://example.com/v1/"),
+            ("https://example.com/apis/", "https://example.com/apis/"),
+        ] {
+            let base_url = normalize_base_url(base_url).unwrap();
+            assert_eq!(default_health_url(&base_url).as_str(), health_url);
+        }
+    }
+
+    #[test]
+    fn rejects_user_agent_set_twice() {
+        let result = FurySDKBuilder::new()
+            .user_agent("mine/1.0")
+            .header("User-Agent", "other/1.0")
+            .build();
+
+        assert!(matches!(result, Err(FuryError::ConfigError(_))));
+    }
+
+    #[tokio::test]
+    async fn sends_configured_user_agent() {
+        assert_eq!(
+            sent_user_agent(FurySDKBuilder::new()).await,
+            DEFAULT_USER_AGENT.to_ascii_lowercase()
+        );
+        assert_eq!(
+            sent_user_agent(FurySDKBuilder::new().user_agent("mine/1.0")).await,
+            "mine/1.0"
+        );
+        assert_eq!(
+            sent_user_agent(FurySDKBuilder::new().header("user-agent", "other/1.0")).await,
+            "other/1.0"
+        );
+    }
+}
@@ -0,0 +1,356 @@
+//! Status of Jito bundles submitted through `send_transactions` with
+//! `Delivery::Jito`, queried from a Jito block engine.
+
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_sdk::signature::Signature;
+use tokio::time::{sleep, Instant};
+
+use crate::{
+    confirmation::Commitment, rpc::JsonRpcClient, sdk::FuryError, serde_utils::display_fromstr_vec,
+};
+
+pub const DEFAULT_BLOCK_ENGINE_URL: &str = "https://mainnet.block-engine.jito.wtf/api/v1/bundles";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BundleStatus {
+    /// Accepted by the block engine and not landed yet
+    Pending,
+    Landed {
+        slot: u64,
+        /// Commitment reached, if the block engine reported it
+        commitment: Option<Commitment>,
+    },
+    /// Rejected by the block engine or failed on chain. `error` is the
+    /// on-chain error, if any.
+    Failed { error: Option<Value> },
+    /// Unknown to the block engine, e.g. expired or never forwarded to a
+    /// leader
+    Dropped,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleStatusReport {
+    pub bundle_id: String,
+    pub status: BundleStatus,
+    /// Signatures of the bundle's transactions, known once it landed
+    pub signatures: Vec<Signature>,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    value: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+enum InflightStatus {
+    Invalid,
+    Pending,
+    Failed,
+    Landed,
+}
+
+#[derive(Deserialize)]
+struct InflightBundleStatus {
+    status: InflightStatus,
+    landed_slot: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct LandedBundleStatus {
+    #[serde(deserialize_with = "display_fromstr_vec::deserialize")]
+    transactions: Vec<Signature>,
+    slot: u64,
+    confirmation_status: Option<Commitment>,
+    /// `{"Ok": null}` on success
+    err: Option<Value>,
+}
+
+/// Queries `getInflightBundleStatuses` and `getBundleStatuses` on a Jito
+/// block engine
+#[derive(Debug, Clone)]
+pub struct BundleTracker {
+    rpc: JsonRpcClient,
+    /// Time `wait_for_bundle` waits for a bundle to leave `Pending`
+    pub timeout: Duration,
+    /// The block engine rate limits status requests, keep this well above
+    /// one second
+    pub poll_interval: Duration,
+}
+
+impl BundleTracker {
+    /// Tracker for the block engine bundle endpoint at `block_engine_url`,
+    /// e.g. `DEFAULT_BLOCK_ENGINE_URL` or a regional engine
+    pub fn new(client: Client, block_engine_url: &str) -> Result<Self, FuryError> {
+        Ok(Self {
+            rpc: JsonRpcClient::new(client, block_engine_url)?,
+            timeout: Duration::from_secs(60),
+            poll_interval: Duration::from_secs(2),
+        })
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Current status of a bundle, without waiting
+    pub async fn get_bundle_status(
+        &self,
+        bundle_id: &str,
+    ) -> Result<BundleStatusReport, FuryError> {
+        let inflight: RpcResponse<Vec<Option<InflightBundleStatus>>> = self
+            .rpc
+            .call("getInflightBundleStatuses", json!([[bundle_id]]))
+            .await?;
+        let inflight = inflight.value.into_iter().next().flatten();
+
+        let report = |status| BundleStatusReport {
+            bundle_id: bundle_id.to_string(),
+            status,
+            signatures: Vec::new(),
+        };
+        match inflight.as_ref().map(|inflight| inflight.status) {
+            Some(InflightStatus::Pending) => return Ok(report(BundleStatus::Pending)),
+            Some(InflightStatus::Failed) => {
+                return Ok(report(BundleStatus::Failed { error: None }))
+            }
+            // Landed bundles carry their signatures in `getBundleStatuses`.
+            // Inflight statuses only cover the last five minutes, so an
+            // unknown bundle may also have landed earlier.
+            Some(InflightStatus::Landed) | Some(InflightStatus::Invalid) | None => {}
+        }
+
+        let landed: RpcResponse<Vec<Option<LandedBundleStatus>>> = self
+            .rpc
+            .call("getBundleStatuses", json!([[bundle_id]]))
+            .await?;
+        let Some(landed) = landed.value.into_iter().next().flatten() else {
+            return Ok(report(match inflight {
+                // Landed according to the inflight status but not indexed yet
+                Some(InflightBundleStatus {
+                    status: InflightStatus::Landed,
+                    landed_slot: Some(slot),
+                }) => BundleStatus::Landed {
+                    slot,
+                    commitment: None,
+                },
+                Some(InflightBundleStatus {
+                    status: InflightStatus::Landed,
+                    ..
+                }) => BundleStatus::Pending,
+                _ => BundleStatus::Dropped,
+            }));
+        };
+
+        let failed = landed
+            .err
+            .as_ref()
+            .is_some_and(|err| err.get("Ok").is_none());
+        Ok(BundleStatusReport {
+            bundle_id: bundle_id.to_string(),
+            status: if failed {
+                BundleStatus::Failed { error: landed.err }
+            } else {
+                BundleStatus::Landed {
+                    slot: landed.slot,
+                    commitment: landed.confirmation_status,
+                }
+            },
+            signatures: landed.transactions,
+        })
+    }
+
+    /// Polls until the bundle lands, fails or is dropped. Returns the last
+    /// `Pending` report if the timeout elapses first.
+    pub async fn wait_for_bundle(&self, bundle_id: &str) -> Result<BundleStatusReport, FuryError> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let report = match self.get_bundle_status(bundle_id).await {
+                Ok(report) => Some(report),
+                // Rate limiting and transient errors are retried until the
+                // deadline
+                Err(e) if e.is_retryable() => None,
+                Err(e) => return Err(e),
+            };
+            match report {
+                Some(report) if report.status != BundleStatus::Pending => return Ok(report),
+                report if Instant::now() >= deadline => {
+                    return Ok(report.unwrap_or_else(|| BundleStatusReport {
+                        bundle_id: bundle_id.to_string(),
+                        status: BundleStatus::Pending,
+                        signatures: Vec::new(),
+                    }))
+                }
+                _ => sleep(self.poll_interval).await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+    use crate::rpc::mock::{result, MockRpcServer};
+
+    const BUNDLE: &str = "bundle";
+
+    fn inflight(status: &str, landed_slot: Option<u64>) -> Value {
+        json!({
+            "bundle_id": BUNDLE,
+            "status": status,
+            "landed_slot": landed_slot,
+        })
+    }
+
+    fn landed(signature: &Signature, err: Value) -> Value {
+        json!({
+            "bundle_id": BUNDLE,
+            "transactions": [signature.to_string()],
+            "slot": 42,
+            "confirmation_status": "confirmed",
+            "err": err,
+        })
+    }
+
+    /// Tracker against a block engine answering with the given statuses,
+    /// `Value::Null` for an unknown bundle
+    async fn block_engine(inflight: Value, landed: Value) -> (BundleTracker, MockRpcServer) {
+        let server = MockRpcServer::start(move |method, _| {
+            let value = match method {
+                "getInflightBundleStatuses" => &inflight,
+                _ => &landed,
+            };
+            result(json!({ "context": { "slot": 50 }, "value": [value] }))
+        })
+        .await;
+        let tracker = BundleTracker::new(Client::new(), &server.url).unwrap();
+        (tracker, server)
+    }
+
+    #[tokio::test]
+    async fn reports_pending_without_querying_landed_bundles() {
+        let (tracker, server) = block_engine(inflight("Pending", None), Value::Null).await;
+
+        let report = tracker.get_bundle_status(BUNDLE).await.unwrap();
+        assert_eq!(report.status, BundleStatus::Pending);
+        assert_eq!(server.calls(), ["getInflightBundleStatuses"]);
+    }
+
+    #[tokio::test]
+    async fn reports_landed_slot_and_signatures() {
+        let signature = Signature::new_unique();
+        let (tracker, _server) = block_engine(
+            inflight("Landed", Some(42)),
+            landed(&signature, json!({ "Ok": null })),
+        )
+        .await;
+
+        let report = tracker.get_bundle_status(BUNDLE).await.unwrap();
+        assert_eq!(
+            report,
+            BundleStatusReport {
+                bundle_id: BUNDLE.to_string(),
+                status: BundleStatus::Landed {
+                    slot: 42,
+                    commitment: Some(Commitment::Confirmed),
+                },
+                signatures: vec![signature],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_failed_bundles() {
+        let (tracker, server) = block_engine(inflight("Failed", None), Value::Null).await;
+        let report = tracker.get_bundle_status(BUNDLE).await.unwrap();
+        assert_eq!(report.status, BundleStatus::Failed { error: None });
+        assert_eq!(server.calls(), ["getInflightBundleStatuses"]);
+
+        // Landed, but failed on chain
+        let error = json!({ "InstructionError": [0, "InvalidAccountData"] });
+        let (tracker, _server) =
+            block_engine(Value::Null, landed(&Signature::new_unique(), error.clone())).await;
+        let report = tracker.get_bundle_status(BUNDLE).await.unwrap();
+        assert_eq!(report.status, BundleStatus::Failed { error: Some(error) });
+    }
+
+    #[tokio::test]
+    async fn reports_unknown_bundles_as_dropped() {
+        for status in [Value::Null, inflight("Invalid", None)] {
+            let (tracker, server) = block_engine(status, Value::Null).await;
+
+            let report = tracker.get_bundle_status(BUNDLE).await.unwrap();
+            assert_eq!(report.status, BundleStatus::Dropped);
+            assert_eq!(
+                server.calls(),
+                ["getInflightBundleStatuses", "getBundleStatuses"]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn trusts_inflight_landing_before_it_is_indexed() {
+        let (tracker, _server) = block_engine(inflight("Landed", Some(7)), Value::Null).await;
+        let report = tracker.get_bundle_status(BUNDLE).await.unwrap();
+        assert_eq!(
+            report.status,
+            BundleStatus::Landed {
+                slot: 7,
+                commitment: None
+            }
+        );
+        assert!(report.signatures.is_empty());
+
+        // Without a slot there is nothing to report yet
+        let (tracker, _server) = block_engine(inflight("Landed", None), Value::Null).await;
+        let report = tracker.get_bundle_status(BUNDLE).await.unwrap();
+        assert_eq!(report.status, BundleStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn waits_until_the_bundle_leaves_pending() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let server = MockRpcServer::start({
+            let polls = polls.clone();
+            move |method, _| {
+                let status = match method {
+                    "getInflightBundleStatuses" if polls.fetch_add(1, Ordering::SeqCst) < 2 => {
+                        inflight("Pending", None)
+                    }
+                    "getInflightBundleStatuses" => inflight("Landed", Some(9)),
+                    _ => Value::Null,
+                };
+                result(json!({ "context": { "slot": 50 }, "value": [status] }))
+            }
+        })
+        .await;
+        let tracker = BundleTracker::new(Client::new(), &server.url)
+            .unwrap()
+            .with_poll_interval(Duration::from_millis(10));
+
+        let report = tracker.wait_for_bundle(BUNDLE).await.unwrap();
+        assert_eq!(
+            report.status,
+            BundleStatus::Landed {
+                slot: 9,
+                commitment: None
+            }
+        );
+        assert_eq!(polls.load(Ordering::SeqCst), 3);
+    }
+}
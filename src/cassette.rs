@@ -0,0 +1,560 @@
+//! Record-and-replay transport for `FurySDK`. A session against the real API
+//! is recorded to a cassette file once, then replayed in tests without
+//! network access.
+//!
+//! ```no_run
+//! # use fury_sdk::{cassette::Cassette, sdk::FurySDK};
+//! # async fn run() -> anyhow::Result<()> {
+//! // Once, against the real API
+//! let cassette = Cassette::record("tests/cassettes/buy.json").with_redacted_field("affiliateAddress");
+//! let fury = FurySDK::builder().api_key("my-api-key").cassette(cassette).build()?;
+//!
+//! // In tests
+//! let fury = FurySDK::new(reqwest::Client::new())
+//!     .with_cassette(Cassette::replay("tests/cassettes/buy.json")?);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, COOKIE, SET_COOKIE},
+    RequestBuilder, Response,
+};
+use serde::{de, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::builder::API_KEY_HEADER;
+
+/// Replaces redacted header values and JSON fields
+pub const REDACTED: &str = "[REDACTED]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Requests go to the network and every exchange is written to the file
+    Record,
+    /// Requests are answered from the file and never reach the network
+    Replay,
+}
+
+#[derive(Debug)]
+pub enum CassetteError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// Replay found no unused interaction for the request
+    NoMatch {
+        method: String,
+        url: String,
+    },
+    /// The request failed while recording
+    Request(reqwest::Error),
+}
+
+impl Display for CassetteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CassetteError::Io(e) => write!(f, "Cassette IO error: {}", e),
+            CassetteError::Json(e) => write!(f, "Invalid cassette: {}", e),
+            CassetteError::NoMatch { method, url } => {
+                write!(f, "No recorded interaction for {} {}", method, url)
+            }
+            CassetteError::Request(e) => write!(f, "Request error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CassetteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CassetteError::Io(e) => Some(e),
+            CassetteError::Json(e) => Some(e),
+            CassetteError::Request(e) => Some(e),
+            CassetteError::NoMatch { .. } => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+/// Contents of a cassette file
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CassetteFile {
+    pub interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    interactions: Vec<Interaction>,
+    /// Interactions already replayed, by index
+    used: Vec<bool>,
+}
+
+/// Records or replays the HTTP exchanges of a `FurySDK`.
+///
+/// Every attempt is an exchange of its own, so retries replay the same
+/// way. Replay answers each request with the first unused interaction of
+/// the same method, URL and, unless turned off, body. Redacted fields of
+/// the recording match any value.
+///
+/// Redacted headers and JSON fields are replaced with `REDACTED` before
+/// anything is written, in requests and responses alike. Headers the client
+/// adds itself, such as the API key and bearer token set on the builder,
+/// are never seen and so never written.
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    pub redacted_headers: HashSet<HeaderName>,
+    pub redacted_fields: HashSet<String>,
+    /// Whether replay also compares request bodies. Turn off when bodies
+    /// vary between runs, e.g. transactions signed by fresh keypairs.
+    pub match_body: bool,
+    state: Mutex<State>,
+}
+
+impl Cassette {
+    /// Records to `path`, which is overwritten on the first exchange
+    pub fn record(path: impl AsRef<Path>) -> Self {
+        Self::new(path.as_ref(), CassetteMode::Record, State::default())
+    }
+
+    /// Loads the interactions recorded in `path`
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self, CassetteError> {
+        let file = fs::read_to_string(path.as_ref()).map_err(CassetteError::Io)?;
+        let file: CassetteFile = serde_json::from_str(&file).map_err(CassetteError::Json)?;
+        for interaction in &file.interactions {
+            let status = interaction.response.status;
+            if http::StatusCode::from_u16(status).is_err() {
+                let error = de::Error::custom(format!("invalid status {}", status));
+                return Err(CassetteError::Json(error));
+            }
+        }
+        let state = State {
+            used: vec![false; file.interactions.len()],
+            interactions: file.interactions,
+        };
+        Ok(Self::new(path.as_ref(), CassetteMode::Replay, state))
+    }
+
+    fn new(path: &Path, mode: CassetteMode, state: State) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            mode,
+            redacted_headers: [
+                AUTHORIZATION,
+                COOKIE,
+                SET_COOKIE,
+                HeaderName::from_static(API_KEY_HEADER),
+            ]
+            .into_iter()
+            .collect(),
+            redacted_fields: HashSet::new(),
+            match_body: true,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Header to redact, in addition to `authorization`, `x-api-key`,
+    /// `cookie` and `set-cookie`. Invalid names are ignored.
+    pub fn with_redacted_header(mut self, name: &str) -> Self {
+        if let Ok(name) = HeaderName::from_bytes(name.as_bytes()) {
+            self.redacted_headers.insert(name);
+        }
+        self
+    }
+
+    /// JSON field to redact at any depth of request and response bodies
+    pub fn with_redacted_field(mut self, name: impl Into<String>) -> Self {
+        self.redacted_fields.insert(name.into());
+        self
+    }
+
+    pub fn with_match_body(mut self, match_body: bool) -> Self {
+        self.match_body = match_body;
+        self
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Interactions recorded so far, or loaded for replay
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.state.lock().unwrap().interactions.clone()
+    }
+
+    /// Interactions not replayed yet. Always 0 while recording.
+    pub fn remaining(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap()
+            .used
+            .iter()
+            .filter(|used| !**used)
+            .count()
+    }
+
+    /// Sends `request`, or answers it from the cassette
+    pub(crate) async fn send(&self, request: RequestBuilder) -> Result<Response, CassetteError> {
+        let (client, request) = request.build_split();
+        let request = request.map_err(CassetteError::Request)?;
+        let recorded = RecordedRequest {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            headers: self.redact_headers(request.headers()),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|body| self.redact_body(body)),
+        };
+
+        match self.mode {
+            CassetteMode::Replay => self.replay_response(&recorded),
+            CassetteMode::Record => {
+                let response = client
+                    .execute(request)
+                    .await
+                    .map_err(CassetteError::Request)?;
+                let status = response.status();
+                let headers = response.headers().clone();
+                let body = response.bytes().await.map_err(CassetteError::Request)?;
+
+                self.save(Interaction {
+                    request: recorded,
+                    response: RecordedResponse {
+                        status: status.as_u16(),
+                        headers: self.redact_headers(&headers),
+                        body: self.redact_body(&body),
+                    },
+                })?;
+
+                // Hand on the response as received, not as redacted
+                let mut response = http::Response::new(body);
+                *response.status_mut() = status;
+                *response.headers_mut() = headers;
+                Ok(response.into())
+            }
+        }
+    }
+
+    fn replay_response(&self, request: &RecordedRequest) -> Result<Response, CassetteError> {
+        let mut state = self.state.lock().unwrap();
+        let State { interactions, used } = &mut *state;
+        let index = interactions
+            .iter()
+            .zip(used.iter())
+            .position(|(interaction, used)| !used && self.matches(&interaction.request, request))
+            .ok_or_else(|| CassetteError::NoMatch {
+                method: request.method.clone(),
+                url: request.url.clone(),
+            })?;
+        used[index] = true;
+
+        let recorded = &interactions[index].response;
+        let mut response = http::Response::new(recorded.body.clone());
+        // Checked when the cassette was loaded
+        *response.status_mut() = http::StatusCode::from_u16(recorded.status).unwrap_or_default();
+        for (name, value) in &recorded.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                response.headers_mut().append(name, value);
+            }
+        }
+        Ok(response.into())
+    }
+
+    fn matches(&self, recorded: &RecordedRequest, request: &RecordedRequest) -> bool {
+        recorded.method == request.method
+            && recorded.url == request.url
+            && (!self.match_body || same_body(&recorded.body, &request.body))
+    }
+
+    /// Adds `interaction` and rewrites the whole file, so a crashed session
+    /// keeps everything recorded before the crash
+    fn save(&self, interaction: Interaction) -> Result<(), CassetteError> {
+        let mut state = self.state.lock().unwrap();
+        state.interactions.push(interaction);
+        let file = CassetteFile {
+            interactions: state.interactions.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file).map_err(CassetteError::Json)?;
+        fs::write(&self.path, json).map_err(CassetteError::Io)
+    }
+
+    fn redact_headers(&self, headers: &HeaderMap) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .filter_map(|(name, value)| {
+                let value = if self.redacted_headers.contains(name) {
+                    REDACTED
+                } else {
+                    value.to_str().ok()?
+                };
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect()
+    }
+
+    fn redact_body(&self, body: &[u8]) -> String {
+        match serde_json::from_slice::<Value>(body) {
+            Ok(mut json) if !self.redacted_fields.is_empty() => {
+                redact_fields(&mut json, &self.redacted_fields);
+                json.to_string()
+            }
+            _ => String::from_utf8_lossy(body).into_owned(),
+        }
+    }
+}
+
+/// Bodies are compared as JSON when both parse, so key order does not
+/// matter. Redacted fields of the recording match any value.
+fn same_body(recorded: &Option<String>, body: &Option<String>) -> bool {
+    match (recorded, body) {
+        (Some(recorded), Some(body)) => match (
+            serde_json::from_str::<Value>(recorded),
+            serde_json::from_str::<Value>(body),
+        ) {
+            (Ok(recorded), Ok(body)) => same_json(&recorded, &body),
+            _ => recorded == body,
+        },
+        (recorded, body) => recorded == body,
+    }
+}
+
+fn same_json(recorded: &Value, value: &Value) -> bool {
+    match (recorded, value) {
+        (Value::String(recorded), _) if recorded == REDACTED => true,
+        (Value::Object(recorded), Value::Object(map)) => {
+            recorded.len() == map.len()
+                && recorded
+                    .iter()
+                    .all(|(key, recorded)| map.get(key).is_some_and(|v| same_json(recorded, v)))
+        }
+        (Value::Array(recorded), Value::Array(values)) => {
+            recorded.len() == values.len()
+                && recorded.iter().zip(values).all(|(r, v)| same_json(r, v))
+        }
+        _ => recorded == value,
+    }
+}
+
+fn redact_fields(value: &mut Value, fields: &HashSet<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if fields.contains(key) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_fields(value, fields);
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                redact_fields(value, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Client;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        rpc::mock::{result, MockRpcServer},
+        sdk::{FuryError, FurySDK},
+    };
+
+    /// Cassette file unique to the test, removed when dropped
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            let file = format!("fury_sdk-{}-{}.json", name, std::process::id());
+            Self(std::env::temp_dir().join(file))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn exchange(method: &str, url: &str, body: Option<Value>, response: &str) -> Interaction {
+        Interaction {
+            request: RecordedRequest {
+                method: method.to_string(),
+                url: url.to_string(),
+                headers: Vec::new(),
+                body: body.map(|body| body.to_string()),
+            },
+            response: RecordedResponse {
+                status: 200,
+                headers: Vec::new(),
+                body: response.to_string(),
+            },
+        }
+    }
+
+    fn replay(file: &TempFile, interactions: Vec<Interaction>) -> Cassette {
+        let json = serde_json::to_string(&CassetteFile { interactions }).unwrap();
+        fs::write(&file.0, json).unwrap();
+        Cassette::replay(&file.0).unwrap()
+    }
+
+    async fn post(cassette: &Cassette, url: &str, body: Value) -> Result<String, CassetteError> {
+        let response = cassette.send(Client::new().post(url).json(&body)).await?;
+        Ok(response.text().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn redacts_secrets_before_writing() {
+        let server =
+            MockRpcServer::start(|_, _| result(json!({ "session": "server-secret", "slot": 1 })))
+                .await;
+        let file = TempFile::new("record");
+        let cassette = Cassette::record(&file.0)
+            .with_redacted_header("x-custom")
+            .with_redacted_field("session");
+
+        let request = Client::new()
+            .post(&server.url)
+            .header(AUTHORIZATION, "Bearer bearer-secret")
+            .header(API_KEY_HEADER, "api-key-secret")
+            .header("x-custom", "custom-secret")
+            .json(&json!({ "method": "getSlot", "params": { "session": "client-secret" } }));
+        let response = cassette.send(request).await.unwrap();
+        // The caller gets the response as received
+        assert!(response.text().await.unwrap().contains("server-secret"));
+
+        let saved = fs::read_to_string(&file.0).unwrap();
+        for secret in [
+            "bearer-secret",
+            "api-key-secret",
+            "custom-secret",
+            "client-secret",
+            "server-secret",
+        ] {
+            assert!(!saved.contains(secret), "{} written to {}", secret, saved);
+        }
+        let saved: CassetteFile = serde_json::from_str(&saved).unwrap();
+        let request = &saved.interactions[0].request;
+        for name in ["authorization", API_KEY_HEADER, "x-custom"] {
+            assert!(request
+                .headers
+                .contains(&(name.to_string(), REDACTED.to_string())));
+        }
+        let body: Value = serde_json::from_str(request.body.as_ref().unwrap()).unwrap();
+        assert_eq!(body["params"]["session"], REDACTED);
+        let body: Value = serde_json::from_str(&saved.interactions[0].response.body).unwrap();
+        assert_eq!(body["result"], json!({ "session": REDACTED, "slot": 1 }));
+    }
+
+    #[tokio::test]
+    async fn replays_by_method_url_and_body() {
+        let url = "http://127.0.0.1:1/buy";
+        let file = TempFile::new("replay");
+        let cassette = replay(
+            &file,
+            vec![
+                exchange("POST", url, Some(json!({ "amount": 1 })), "one"),
+                exchange("POST", url, Some(json!({ "amount": 2 })), "two"),
+                exchange("POST", url, Some(json!({ "key": REDACTED })), "any key"),
+                exchange("GET", url, None, "get"),
+            ],
+        );
+
+        assert_eq!(
+            post(&cassette, url, json!({ "amount": 2 })).await.unwrap(),
+            "two"
+        );
+        assert_eq!(
+            post(&cassette, url, json!({ "amount": 1 })).await.unwrap(),
+            "one"
+        );
+        assert_eq!(
+            post(&cassette, url, json!({ "key": "anything" }))
+                .await
+                .unwrap(),
+            "any key"
+        );
+        assert_eq!(cassette.remaining(), 1);
+
+        // Each interaction is replayed once
+        let replayed = post(&cassette, url, json!({ "amount": 1 })).await;
+        assert!(matches!(replayed, Err(CassetteError::NoMatch { .. })));
+        let other_url = post(&cassette, "http://127.0.0.1:1/sell", json!({ "amount": 2 })).await;
+        assert!(matches!(other_url, Err(CassetteError::NoMatch { .. })));
+        let get = cassette.send(Client::new().get(url)).await.unwrap();
+        assert_eq!(get.text().await.unwrap(), "get");
+    }
+
+    #[tokio::test]
+    async fn replays_without_body_matching() {
+        let url = "http://127.0.0.1:1/buy";
+        let file = TempFile::new("replay-any-body");
+        let cassette = replay(
+            &file,
+            vec![exchange("POST", url, Some(json!({ "amount": 1 })), "one")],
+        )
+        .with_match_body(false);
+
+        assert_eq!(
+            post(&cassette, url, json!({ "amount": 5 })).await.unwrap(),
+            "one"
+        );
+    }
+
+    #[tokio::test]
+    async fn sdk_reports_missing_interaction() {
+        let file = TempFile::new("replay-empty");
+        let fury = FurySDK::new(Client::new()).with_cassette(replay(&file, Vec::new()));
+
+        let error = fury.health_check().await.unwrap_err();
+        assert!(
+            matches!(
+                error,
+                FuryError::CassetteError(CassetteError::NoMatch { ref method, .. }) if method == "GET"
+            ),
+            "{:?}",
+            error
+        );
+    }
+}
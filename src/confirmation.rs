@@ -0,0 +1,539 @@
+//! Tracks sent transactions until they land, fail or expire by polling a
+//! Solana JSON-RPC endpoint.
+//!
+//! ```no_run
+//! # use fury_sdk::confirmation::{Commitment, ConfirmationTracker};
+//! # async fn run(signature: solana_sdk::signature::Signature) -> Result<(), fury_sdk::sdk::FuryError> {
+//! let tracker = ConfirmationTracker::new(reqwest::Client::new(), "http://127.0.0.1:8899")?
+//!     .with_commitment(Commitment::Finalized);
+//! let status = tracker.confirm(&signature, None).await?;
+//! println!("{:?}", status);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_sdk::{hash::Hash, signature::Signature, transaction::VersionedTransaction};
+use tokio::time::{sleep, timeout_at, Instant};
+
+use crate::{rpc::JsonRpcClient, sdk::FuryError, serde_utils::display_fromstr};
+
+/// `getSignatureStatuses` accepts at most this many signatures per call
+const MAX_SIGNATURES_PER_REQUEST: usize = 256;
+
+/// How far the cluster must have progressed before a transaction counts as
+/// landed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    Processed,
+    #[default]
+    Confirmed,
+    Finalized,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfirmationStatus {
+    /// The node has not seen the transaction yet
+    Pending,
+    Processed {
+        slot: u64,
+    },
+    Confirmed {
+        slot: u64,
+    },
+    Finalized {
+        slot: u64,
+    },
+    /// The transaction landed but failed. `error` is the `TransactionError`
+    /// as reported by the node, e.g. `{"InstructionError":[0,{"Custom":1}]}`.
+    Failed {
+        slot: u64,
+        error: Value,
+    },
+    /// The block height passed the last valid block height of the
+    /// transaction's blockhash before it was seen, so it can no longer land
+    Expired,
+    /// The tracker gave up before the transaction reached the requested
+    /// commitment. Carries the last status observed.
+    TimedOut {
+        last: Box<ConfirmationStatus>,
+    },
+}
+
+impl ConfirmationStatus {
+    /// Whether the transaction succeeded at `commitment` or better
+    pub fn reached(&self, commitment: Commitment) -> bool {
+        let landed = match self {
+            ConfirmationStatus::Processed { .. } => Commitment::Processed,
+            ConfirmationStatus::Confirmed { .. } => Commitment::Confirmed,
+            ConfirmationStatus::Finalized { .. } => Commitment::Finalized,
+            _ => return false,
+        };
+        landed >= commitment
+    }
+
+    /// Slot the transaction landed in, if it was seen
+    pub fn slot(&self) -> Option<u64> {
+        match self {
+            ConfirmationStatus::Processed { slot }
+            | ConfirmationStatus::Confirmed { slot }
+            | ConfirmationStatus::Finalized { slot }
+            | ConfirmationStatus::Failed { slot, .. } => Some(*slot),
+            ConfirmationStatus::TimedOut { last } => last.slot(),
+            ConfirmationStatus::Pending | ConfirmationStatus::Expired => None,
+        }
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(self, ConfirmationStatus::Failed { .. })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcSignatureStatus {
+    slot: u64,
+    #[serde(default)]
+    err: Option<Value>,
+    #[serde(default)]
+    confirmation_status: Option<Commitment>,
+}
+
+impl From<Option<RpcSignatureStatus>> for ConfirmationStatus {
+    fn from(status: Option<RpcSignatureStatus>) -> Self {
+        let Some(status) = status else {
+            return ConfirmationStatus::Pending;
+        };
+        let slot = status.slot;
+        if let Some(error) = status.err {
+            return ConfirmationStatus::Failed { slot, error };
+        }
+        // Older nodes omit `confirmationStatus`; assume the weakest level
+        match status.confirmation_status {
+            Some(Commitment::Finalized) => ConfirmationStatus::Finalized { slot },
+            Some(Commitment::Confirmed) => ConfirmationStatus::Confirmed { slot },
+            Some(Commitment::Processed) | None => ConfirmationStatus::Processed { slot },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    value: T,
+}
+
+/// Polls `getSignatureStatuses` and `getBlockHeight` on a Solana RPC node
+#[derive(Debug, Clone)]
+pub struct ConfirmationTracker {
+    rpc: JsonRpcClient,
+    pub commitment: Commitment,
+    /// Time to wait for the requested commitment
+    pub timeout: Duration,
+    pub poll_interval: Duration,
+}
+
+impl ConfirmationTracker {
+    /// Tracker for the RPC node at `rpc_url`, e.g. a local
+    /// `solana-test-validator` at `http://127.0.0.1:8899`
+    pub fn new(client: Client, rpc_url: &str) -> Result<Self, FuryError> {
+        Ok(Self {
+            rpc: JsonRpcClient::new(client, rpc_url)?,
+            commitment: Commitment::default(),
+            timeout: Duration::from_secs(60),
+            poll_interval: Duration::from_millis(500),
+        })
+    }
+
+    pub fn with_commitment(mut self, commitment: Commitment) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Current status of each signature, without waiting
+    pub async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<ConfirmationStatus>, FuryError> {
+        let mut res = Vec::with_capacity(signatures.len());
+        for chunk in signatures.chunks(MAX_SIGNATURES_PER_REQUEST) {
+            let signatures: Vec<String> = chunk.iter().map(|s| s.to_string()).collect();
+            let statuses: RpcResponse<Vec<Option<RpcSignatureStatus>>> = self
+                .rpc
+                .call(
+                    "getSignatureStatuses",
+                    json!([signatures, { "searchTransactionHistory": false }]),
+                )
+                .await?;
+            res.extend(statuses.value.into_iter().map(ConfirmationStatus::from));
+        }
+        Ok(res)
+    }
+
+    pub async fn get_block_height(&self) -> Result<u64, FuryError> {
+        self.rpc
+            .call("getBlockHeight", json!([{ "commitment": self.commitment }]))
+            .await
+    }
+
+    /// Latest blockhash and the last block height at which it is valid
+    pub async fn get_latest_blockhash(&self) -> Result<(Hash, u64), FuryError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct LatestBlockhash {
+            #[serde(with = "display_fromstr")]
+            blockhash: Hash,
+            last_valid_block_height: u64,
+        }
+
+        let latest: RpcResponse<LatestBlockhash> = self
+            .rpc
+            .call(
+                "getLatestBlockhash",
+                json!([{ "commitment": self.commitment }]),
+            )
+            .await?;
+        Ok((latest.value.blockhash, latest.value.last_valid_block_height))
+    }
+
+    /// Waits until `signature` reaches the tracker's commitment, fails,
+    /// expires or the timeout elapses
+    ///
+    /// # Arguments
+    ///
+    /// * `signature` - First signature of the transaction
+    /// * `last_valid_block_height` - Last block height at which the
+    ///   transaction's blockhash is valid. Without it only the timeout
+    ///   bounds the wait.
+    pub async fn confirm(
+        &self,
+        signature: &Signature,
+        last_valid_block_height: Option<u64>,
+    ) -> Result<ConfirmationStatus, FuryError> {
+        let mut statuses = self
+            .confirm_all(&[*signature], last_valid_block_height)
+            .await?;
+        Ok(statuses.remove(0))
+    }
+
+    /// Waits for every signature, polling them together. Each one resolves
+    /// independently, so one failure does not hide the outcome of the rest.
+    /// RPC calls still running at the timeout are abandoned.
+    pub async fn confirm_all(
+        &self,
+        signatures: &[Signature],
+        last_valid_block_height: Option<u64>,
+    ) -> Result<Vec<ConfirmationStatus>, FuryError> {
+        let deadline = Instant::now() + self.timeout;
+        let mut statuses = vec![ConfirmationStatus::Pending; signatures.len()];
+
+        loop {
+            let pending: Vec<usize> = (0..signatures.len())
+                .filter(|index| !self.is_resolved(&statuses[*index]))
+                .collect();
+            if pending.is_empty() {
+                return Ok(statuses);
+            }
+
+            let pending_signatures: Vec<Signature> =
+                pending.iter().map(|index| signatures[*index]).collect();
+            match timeout_at(deadline, self.get_signature_statuses(&pending_signatures)).await {
+                Ok(Ok(latest)) => {
+                    for (index, status) in pending.iter().zip(latest) {
+                        statuses[*index] = status;
+                    }
+                }
+                // Transient node errors are retried until the deadline
+                Ok(Err(e)) if e.is_retryable() => {}
+                Ok(Err(e)) => return Err(e),
+                // Hung past the deadline, which is handled below
+                Err(_) => {}
+            }
+
+            if let Some(last_valid_block_height) = last_valid_block_height {
+                let unseen = statuses.contains(&ConfirmationStatus::Pending);
+                if unseen {
+                    match timeout_at(deadline, self.get_block_height()).await {
+                        Ok(Ok(block_height)) if block_height > last_valid_block_height => {
+                            self.expire_unseen(signatures, &mut statuses, deadline)
+                                .await?;
+                        }
+                        Ok(Ok(_)) | Err(_) => {}
+                        Ok(Err(e)) if e.is_retryable() => {}
+                        Ok(Err(e)) => return Err(e),
+                    }
+                }
+            }
+
+            if Instant::now() >= deadline {
+                for status in statuses.iter_mut() {
+                    if !self.is_resolved(status) {
+                        *status = ConfirmationStatus::TimedOut {
+                            last: Box::new(status.clone()),
+                        };
+                    }
+                }
+                return Ok(statuses);
+            }
+            sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Waits for signed transactions, identified by their first signature
+    pub async fn confirm_transactions(
+        &self,
+        transactions: &[VersionedTransaction],
+        last_valid_block_height: Option<u64>,
+    ) -> Result<Vec<ConfirmationStatus>, FuryError> {
+        let signatures: Vec<Signature> = transactions
+            .iter()
+            .map(|transaction| transaction.signatures.first().copied().unwrap_or_default())
+            .collect();
+        self.confirm_all(&signatures, last_valid_block_height).await
+    }
+
+    /// Marks transactions still unseen once the blockhash expired as
+    /// `Expired`. Their status is queried once more first, as they may have
+    /// landed after the last poll.
+    async fn expire_unseen(
+        &self,
+        signatures: &[Signature],
+        statuses: &mut [ConfirmationStatus],
+        deadline: Instant,
+    ) -> Result<(), FuryError> {
+        let unseen: Vec<usize> = (0..signatures.len())
+            .filter(|index| statuses[*index] == ConfirmationStatus::Pending)
+            .collect();
+        let unseen_signatures: Vec<Signature> =
+            unseen.iter().map(|index| signatures[*index]).collect();
+        match timeout_at(deadline, self.get_signature_statuses(&unseen_signatures)).await {
+            Ok(Ok(latest)) => {
+                for (index, status) in unseen.iter().zip(latest) {
+                    statuses[*index] = match status {
+                        ConfirmationStatus::Pending => ConfirmationStatus::Expired,
+                        status => status,
+                    };
+                }
+                Ok(())
+            }
+            // Left pending, the next poll tries again or times out
+            Ok(Err(e)) if e.is_retryable() => Ok(()),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Ok(()),
+        }
+    }
+
+    fn is_resolved(&self, status: &ConfirmationStatus) -> bool {
+        match status {
+            ConfirmationStatus::Failed { .. }
+            | ConfirmationStatus::Expired
+            | ConfirmationStatus::TimedOut { .. } => true,
+            status => status.reached(self.commitment),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::rpc::mock::{error, result, MockRpcServer};
+
+    fn tracker(server: &MockRpcServer) -> ConfirmationTracker {
+        ConfirmationTracker::new(Client::new(), &server.url)
+            .unwrap()
+            .with_poll_interval(Duration::from_millis(10))
+            .with_timeout(Duration::from_secs(5))
+    }
+
+    fn status(slot: u64, err: Value, confirmation_status: &str) -> Value {
+        json!({
+            "slot": slot,
+            "confirmations": null,
+            "err": err,
+            "confirmationStatus": confirmation_status,
+        })
+    }
+
+    /// `getSignatureStatuses` result with one entry per signature requested
+    fn statuses(params: &Value, status: Value) -> (u16, Value) {
+        let count = params[0].as_array().map_or(0, Vec::len);
+        result(json!({ "context": { "slot": 1 }, "value": vec![status; count] }))
+    }
+
+    #[tokio::test]
+    async fn waits_until_confirmed() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let server = MockRpcServer::start({
+            let polls = polls.clone();
+            move |_, params| match polls.fetch_add(1, Ordering::SeqCst) {
+                0 => statuses(params, Value::Null),
+                1 => statuses(params, status(7, Value::Null, "processed")),
+                _ => statuses(params, status(7, Value::Null, "confirmed")),
+            }
+        })
+        .await;
+
+        let status = tracker(&server)
+            .confirm(&Signature::new_unique(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(status, ConfirmationStatus::Confirmed { slot: 7 });
+        assert_eq!(polls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn reports_failed_transaction() {
+        let err = json!({ "InstructionError": [0, { "Custom": 1 }] });
+        let server = MockRpcServer::start({
+            let err = err.clone();
+            move |_, params| statuses(params, status(9, err.clone(), "processed"))
+        })
+        .await;
+
+        let status = tracker(&server)
+            .confirm(&Signature::new_unique(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            status,
+            ConfirmationStatus::Failed {
+                slot: 9,
+                error: err
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn expires_after_last_valid_block_height() {
+        let server = MockRpcServer::start(|method, params| match method {
+            "getBlockHeight" => result(json!(101)),
+            _ => statuses(params, Value::Null),
+        })
+        .await;
+
+        let status = tracker(&server)
+            .confirm(&Signature::new_unique(), Some(100))
+            .await
+            .unwrap();
+
+        assert_eq!(status, ConfirmationStatus::Expired);
+        assert_eq!(
+            server.calls(),
+            [
+                "getSignatureStatuses",
+                "getBlockHeight",
+                "getSignatureStatuses"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn rechecks_status_before_expiring() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let server = MockRpcServer::start({
+            let polls = polls.clone();
+            move |method, params| match method {
+                "getBlockHeight" => result(json!(101)),
+                // Lands between the first poll and the block height check
+                _ if polls.fetch_add(1, Ordering::SeqCst) == 0 => statuses(params, Value::Null),
+                _ => statuses(params, status(5, Value::Null, "confirmed")),
+            }
+        })
+        .await;
+
+        let status = tracker(&server)
+            .confirm(&Signature::new_unique(), Some(100))
+            .await
+            .unwrap();
+
+        assert_eq!(status, ConfirmationStatus::Confirmed { slot: 5 });
+    }
+
+    #[tokio::test]
+    async fn surfaces_rpc_errors() {
+        let server = MockRpcServer::start(|_, _| error(-32602, "Invalid params")).await;
+
+        match tracker(&server)
+            .confirm(&Signature::new_unique(), None)
+            .await
+        {
+            Err(FuryError::RpcError(error)) => assert_eq!(error.code, -32602),
+            other => panic!("expected RpcError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn times_out_when_the_node_hangs() {
+        // Accepts connections and never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                connections.push(stream);
+            }
+        });
+        let tracker = ConfirmationTracker::new(Client::new(), &url)
+            .unwrap()
+            .with_timeout(Duration::from_millis(100));
+
+        let status = tokio::time::timeout(
+            Duration::from_secs(5),
+            tracker.confirm(&Signature::new_unique(), Some(100)),
+        )
+        .await
+        .expect("confirm did not return at its timeout")
+        .unwrap();
+
+        assert_eq!(
+            status,
+            ConfirmationStatus::TimedOut {
+                last: Box::new(ConfirmationStatus::Pending)
+            }
+        );
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn times_out_with_last_status() {
+        let server =
+            MockRpcServer::start(|_, params| statuses(params, status(3, Value::Null, "processed")))
+                .await;
+
+        let status = tracker(&server)
+            .with_timeout(Duration::from_millis(50))
+            .confirm(&Signature::new_unique(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            status,
+            ConfirmationStatus::TimedOut {
+                last: Box::new(ConfirmationStatus::Processed { slot: 3 })
+            }
+        );
+    }
+}
@@ -0,0 +1,536 @@
+//! Runs a buy or sell end to end: request the transactions from FURY API,
+//! check them, sign, send and wait for them to land.
+//!
+//! ```no_run
+//! # use fury_sdk::{confirmation::ConfirmationTracker, executor::{TradeExecutor, TradeRequest}, sdk::{BuyTokenRequest, FurySDK}, signer::AsyncSigner};
+//! # async fn run(fury: &FurySDK, request: BuyTokenRequest, wallet: &solana_sdk::signature::Keypair) -> anyhow::Result<()> {
+//! let executor = TradeExecutor::new(fury).with_confirmation(ConfirmationTracker::new(
+//!     reqwest::Client::new(),
+//!     "https://api.mainnet-beta.solana.com",
+//! )?);
+//! let report = executor
+//!     .execute(&TradeRequest::Buy(request), &[wallet as &dyn AsyncSigner])
+//!     .await?;
+//! for timing in &report.timings {
+//!     println!("{}: {:?}", timing.stage, timing.elapsed);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    fmt::{self, Display},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use solana_sdk::{signature::Signature, transaction::VersionedTransaction};
+
+#[cfg(feature = "simulation")]
+use crate::simulation::{SimulationResult, Simulator};
+use crate::{
+    api::FuryApi,
+    confirmation::{ConfirmationStatus, ConfirmationTracker},
+    policy::TransactionPolicy,
+    sdk::{BuyTokenRequest, Delivery, FuryError, FurySDK, SellRequest, SendOutcome},
+    signer::AsyncSigner,
+    utils::SignError,
+};
+
+#[derive(Debug)]
+pub enum TradeRequest {
+    Buy(BuyTokenRequest),
+    Sell(SellRequest),
+}
+
+impl From<BuyTokenRequest> for TradeRequest {
+    fn from(request: BuyTokenRequest) -> Self {
+        TradeRequest::Buy(request)
+    }
+}
+
+impl From<SellRequest> for TradeRequest {
+    fn from(request: SellRequest) -> Self {
+        TradeRequest::Sell(request)
+    }
+}
+
+/// Steps of the pipeline, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    Request,
+    Policy,
+    Simulation,
+    Sign,
+    Send,
+    Confirm,
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Stage::Request => "request",
+            Stage::Policy => "policy",
+            Stage::Simulation => "simulation",
+            Stage::Sign => "sign",
+            Stage::Send => "send",
+            Stage::Confirm => "confirm",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StageTiming {
+    pub stage: Stage,
+    pub elapsed: Duration,
+}
+
+/// Outcome of a successful run. Stages that are turned off leave their
+/// fields empty and have no timing.
+#[derive(Debug)]
+pub struct TradeReport {
+    /// Signed transactions, in the order returned by the API
+    pub transactions: Vec<VersionedTransaction>,
+    #[cfg(feature = "simulation")]
+    pub simulation: Vec<SimulationResult>,
+    pub send: Option<SendOutcome>,
+    /// Status of each transaction. `None` if nothing was sent or no
+    /// `ConfirmationTracker` was configured.
+    pub confirmation: Option<Vec<ConfirmationStatus>>,
+    pub timings: Vec<StageTiming>,
+}
+
+impl TradeReport {
+    pub fn elapsed(&self, stage: Stage) -> Option<Duration> {
+        elapsed(&self.timings, stage)
+    }
+
+    pub fn total(&self) -> Duration {
+        self.timings.iter().map(|timing| timing.elapsed).sum()
+    }
+
+    /// First signature of each transaction, which identifies it on chain
+    pub fn signatures(&self) -> Vec<Signature> {
+        self.transactions
+            .iter()
+            .map(|transaction| transaction.signatures.first().copied().unwrap_or_default())
+            .collect()
+    }
+}
+
+/// A failed run, with the timings of the stages that ran
+#[derive(Debug)]
+pub struct TradeError {
+    pub kind: TradeErrorKind,
+    /// Completed stages followed by the failed one
+    pub timings: Vec<StageTiming>,
+}
+
+impl TradeError {
+    /// Stage the pipeline stopped at
+    pub fn stage(&self) -> Stage {
+        self.kind.stage()
+    }
+
+    pub fn elapsed(&self, stage: Stage) -> Option<Duration> {
+        elapsed(&self.timings, stage)
+    }
+}
+
+impl Display for TradeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
+impl std::error::Error for TradeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.kind.source()
+    }
+}
+
+#[derive(Debug)]
+pub enum TradeErrorKind {
+    /// The buy or sell endpoint failed
+    Request(FuryError),
+    /// A transaction was rejected by the signing policy
+    Policy(SignError),
+    /// A transaction failed in the simulator. Nothing was signed.
+    #[cfg(feature = "simulation")]
+    Simulation {
+        index: usize,
+        result: SimulationResult,
+    },
+    Sign(SignError),
+    /// The send failed or the API did not accept the transactions
+    Send(FuryError),
+    /// The transactions were sent, but their status could not be queried
+    Confirm {
+        transactions: Vec<VersionedTransaction>,
+        send: SendOutcome,
+        source: FuryError,
+    },
+}
+
+impl TradeErrorKind {
+    pub fn stage(&self) -> Stage {
+        match self {
+            TradeErrorKind::Request(_) => Stage::Request,
+            TradeErrorKind::Policy(_) => Stage::Policy,
+            #[cfg(feature = "simulation")]
+            TradeErrorKind::Simulation { .. } => Stage::Simulation,
+            TradeErrorKind::Sign(_) => Stage::Sign,
+            TradeErrorKind::Send(_) => Stage::Send,
+            TradeErrorKind::Confirm { .. } => Stage::Confirm,
+        }
+    }
+}
+
+impl Display for TradeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TradeErrorKind::Request(e) | TradeErrorKind::Send(e) => {
+                write!(f, "{} failed: {}", self.stage(), e)
+            }
+            TradeErrorKind::Policy(e) | TradeErrorKind::Sign(e) => {
+                write!(f, "{} failed: {}", self.stage(), e)
+            }
+            #[cfg(feature = "simulation")]
+            TradeErrorKind::Simulation { index, result } => match &result.error {
+                Some(error) => write!(f, "Transaction {}: simulation failed: {}", index, error),
+                None => write!(f, "Transaction {}: simulation failed", index),
+            },
+            TradeErrorKind::Confirm { source, .. } => {
+                write!(f, "Transactions sent, confirm failed: {}", source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TradeErrorKind {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TradeErrorKind::Request(e) | TradeErrorKind::Send(e) => Some(e),
+            TradeErrorKind::Confirm { source, .. } => Some(source),
+            TradeErrorKind::Policy(e) | TradeErrorKind::Sign(e) => Some(e),
+            #[cfg(feature = "simulation")]
+            TradeErrorKind::Simulation { .. } => None,
+        }
+    }
+}
+
+/// Requests, checks, signs, sends and confirms buy and sell transactions.
+///
+/// Only the request, sign and send stages run by default. Policy checks,
+/// simulation and confirmation run once configured, and sending can be
+/// turned off for dry runs. Any `FuryApi` can serve the requests, e.g.
+/// `fake::FakeFuryApi` in tests.
+pub struct TradeExecutor<'a, A: FuryApi + ?Sized = FurySDK> {
+    sdk: &'a A,
+    pub delivery: Delivery,
+    pub policy: Option<Arc<dyn TransactionPolicy>>,
+    /// Transactions run in order on a copy of the simulator, so later ones
+    /// see the effects of earlier ones
+    #[cfg(feature = "simulation")]
+    pub simulator: Option<Simulator>,
+    pub send: bool,
+    pub confirmation: Option<ConfirmationTracker>,
+}
+
+impl<'a, A: FuryApi + ?Sized> TradeExecutor<'a, A> {
+    pub fn new(sdk: &'a A) -> Self {
+        Self {
+            sdk,
+            delivery: Delivery::default(),
+            policy: None,
+            #[cfg(feature = "simulation")]
+            simulator: None,
+            send: true,
+            confirmation: None,
+        }
+    }
+
+    pub fn with_delivery(mut self, delivery: Delivery) -> Self {
+        self.delivery = delivery;
+        self
+    }
+
+    pub fn with_policy(mut self, policy: impl TransactionPolicy + 'static) -> Self {
+        self.policy = Some(Arc::new(policy));
+        self
+    }
+
+    #[cfg(feature = "simulation")]
+    pub fn with_simulator(mut self, simulator: Simulator) -> Self {
+        self.simulator = Some(simulator);
+        self
+    }
+
+    /// Whether to submit the signed transactions. When off, the run stops
+    /// after signing.
+    pub fn with_send(mut self, send: bool) -> Self {
+        self.send = send;
+        self
+    }
+
+    pub fn with_confirmation(mut self, confirmation: ConfirmationTracker) -> Self {
+        self.confirmation = Some(confirmation);
+        self
+    }
+
+    pub async fn execute(
+        &self,
+        request: &TradeRequest,
+        signers: &[&dyn AsyncSigner],
+    ) -> Result<TradeReport, TradeError> {
+        let mut timings = Vec::new();
+        let mut timer = Timer::start();
+        self.run(request, signers, &mut timings, &mut timer)
+            .await
+            .map_err(|kind| {
+                timings.push(timer.lap(kind.stage()));
+                TradeError { kind, timings }
+            })
+    }
+
+    async fn run(
+        &self,
+        request: &TradeRequest,
+        signers: &[&dyn AsyncSigner],
+        timings: &mut Vec<StageTiming>,
+        timer: &mut Timer,
+    ) -> Result<TradeReport, TradeErrorKind> {
+        let unsigned = match request {
+            TradeRequest::Buy(request) => self.sdk.buy_token(request).await.map(|r| r.transactions),
+            TradeRequest::Sell(request) => {
+                self.sdk.sell_token(request).await.map(|r| r.transactions)
+            }
+        }
+        .map_err(TradeErrorKind::Request)?;
+        timings.push(timer.lap(Stage::Request));
+
+        if let Some(policy) = &self.policy {
+            unsigned
+                .check_policy(policy.as_ref())
+                .map_err(TradeErrorKind::Policy)?;
+            timings.push(timer.lap(Stage::Policy));
+        }
+
+        #[cfg(feature = "simulation")]
+        let simulation = match &self.simulator {
+            Some(simulator) => {
+                let results = simulator.clone().execute_all(&unsigned);
+                if let Some(index) = results.iter().position(|result| !result.is_success()) {
+                    return Err(TradeErrorKind::Simulation {
+                        index,
+                        result: results[index].clone(),
+                    });
+                }
+                timings.push(timer.lap(Stage::Simulation));
+                results
+            }
+            None => Vec::new(),
+        };
+
+        let transactions = unsigned
+            .sign_async(signers)
+            .await
+            .map_err(TradeErrorKind::Sign)?;
+        timings.push(timer.lap(Stage::Sign));
+
+        let mut report = TradeReport {
+            transactions,
+            #[cfg(feature = "simulation")]
+            simulation,
+            send: None,
+            confirmation: None,
+            timings: Vec::new(),
+        };
+        if !self.send {
+            report.timings = std::mem::take(timings);
+            return Ok(report);
+        }
+
+        let send = self
+            .sdk
+            .send_transactions(&report.transactions, self.delivery)
+            .await
+            .and_then(SendOutcome::into_result)
+            .map_err(TradeErrorKind::Send)?;
+        timings.push(timer.lap(Stage::Send));
+
+        if let Some(tracker) = &self.confirmation {
+            match confirm(tracker, &report.transactions).await {
+                Ok(statuses) => report.confirmation = Some(statuses),
+                Err(source) => {
+                    return Err(TradeErrorKind::Confirm {
+                        transactions: report.transactions,
+                        send,
+                        source,
+                    })
+                }
+            }
+            timings.push(timer.lap(Stage::Confirm));
+        }
+        report.send = Some(send);
+        report.timings = std::mem::take(timings);
+        Ok(report)
+    }
+}
+
+/// Waits for the sent transactions. The API does not return the last valid
+/// block height of their blockhash, so the one of the latest blockhash
+/// stands in: the transactions were built earlier and expire no later.
+async fn confirm(
+    tracker: &ConfirmationTracker,
+    transactions: &[VersionedTransaction],
+) -> Result<Vec<ConfirmationStatus>, FuryError> {
+    let (_, last_valid_block_height) = tracker.get_latest_blockhash().await?;
+    tracker
+        .confirm_transactions(transactions, Some(last_valid_block_height))
+        .await
+}
+
+fn elapsed(timings: &[StageTiming], stage: Stage) -> Option<Duration> {
+    timings
+        .iter()
+        .find(|timing| timing.stage == stage)
+        .map(|timing| timing.elapsed)
+}
+
+struct Timer(Instant);
+
+impl Timer {
+    fn start() -> Self {
+        Self(Instant::now())
+    }
+
+    fn lap(&mut self, stage: Stage) -> StageTiming {
+        let now = Instant::now();
+        let elapsed = now - self.0;
+        self.0 = now;
+        StageTiming { stage, elapsed }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+    use super::*;
+    use crate::{
+        api::Endpoint,
+        fake::FakeFuryApi,
+        rpc::mock::{result, MockRpcServer},
+        sdk::BuyTokenResponse,
+        test_support::{buy_request, jito_sent, unsigned_transfer},
+        transactions::UnsignedTransactions,
+    };
+
+    /// Queues the API response of a buy by `wallet` and returns the request
+    fn buy(fake: &FakeFuryApi, wallet: &Pubkey) -> TradeRequest {
+        fake.push_response(BuyTokenResponse {
+            success: true,
+            transactions: UnsignedTransactions::new(vec![unsigned_transfer(
+                wallet,
+                wallet,
+                &Pubkey::new_unique(),
+                1_000,
+            )]),
+        });
+        TradeRequest::Buy(buy_request(&[*wallet]))
+    }
+
+    fn stages(timings: &[StageTiming]) -> Vec<Stage> {
+        timings.iter().map(|timing| timing.stage).collect()
+    }
+
+    #[tokio::test]
+    async fn confirms_against_latest_blockhash_expiry() {
+        let wallet = Keypair::new();
+        let fake = FakeFuryApi::new();
+        let request = buy(&fake, &wallet.pubkey());
+        fake.push_response(jito_sent(true));
+        let server = MockRpcServer::start(|method, params| match method {
+            "getLatestBlockhash" => result(json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "blockhash": Hash::new_unique().to_string(),
+                    "lastValidBlockHeight": 100,
+                },
+            })),
+            "getBlockHeight" => result(json!(101)),
+            _ => {
+                let count = params[0].as_array().map_or(0, Vec::len);
+                result(json!({ "context": { "slot": 1 }, "value": vec![Value::Null; count] }))
+            }
+        })
+        .await;
+        let tracker = ConfirmationTracker::new(reqwest::Client::new(), &server.url)
+            .unwrap()
+            .with_poll_interval(Duration::from_millis(10))
+            // Only the block height can end the wait in time
+            .with_timeout(Duration::from_secs(60));
+
+        let report = TradeExecutor::new(&fake)
+            .with_confirmation(tracker)
+            .execute(&request, &[&wallet as &dyn AsyncSigner])
+            .await
+            .unwrap();
+
+        assert_eq!(report.confirmation, Some(vec![ConfirmationStatus::Expired]));
+        assert_eq!(server.calls()[0], "getLatestBlockhash");
+        assert_eq!(
+            stages(&report.timings),
+            [Stage::Request, Stage::Sign, Stage::Send, Stage::Confirm]
+        );
+    }
+
+    #[tokio::test]
+    async fn fails_when_send_is_not_accepted() {
+        let wallet = Keypair::new();
+        let fake = FakeFuryApi::new();
+        let request = buy(&fake, &wallet.pubkey());
+        fake.push_response(jito_sent(false));
+
+        let error = TradeExecutor::new(&fake)
+            .execute(&request, &[&wallet as &dyn AsyncSigner])
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error.kind,
+            TradeErrorKind::Send(FuryError::ApiError(_))
+        ));
+        assert_eq!(
+            stages(&error.timings),
+            [Stage::Request, Stage::Sign, Stage::Send]
+        );
+    }
+
+    #[tokio::test]
+    async fn keeps_timings_when_a_stage_fails() {
+        let wallet = Keypair::new();
+        let fake = FakeFuryApi::new();
+        let request = buy(&fake, &wallet.pubkey());
+        fake.push_error(
+            Endpoint::SendTransactions,
+            FuryError::ConfigError("down".to_string()),
+        );
+
+        let error = TradeExecutor::new(&fake)
+            .execute(&request, &[&wallet as &dyn AsyncSigner])
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error.kind, TradeErrorKind::Send(_)));
+        assert_eq!(error.stage(), Stage::Send);
+        assert_eq!(
+            stages(&error.timings),
+            [Stage::Request, Stage::Sign, Stage::Send]
+        );
+        assert!(error.elapsed(Stage::Request).is_some());
+    }
+}
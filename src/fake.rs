@@ -0,0 +1,286 @@
+//! In-memory `FuryApi` for tests. Responses are scripted per endpoint and
+//! every call is recorded.
+//!
+//! ```
+//! # use fury_sdk::{api::{Endpoint, FuryApi}, fake::FakeFuryApi, sdk::{FuryError, GenerateMintResponse}};
+//! # use solana_sdk::pubkey::Pubkey;
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let fake = FakeFuryApi::new();
+//! let mint = Pubkey::new_unique();
+//! fake.push_response(GenerateMintResponse { pubkey: mint });
+//! fake.push_error(Endpoint::GenerateMint, FuryError::ConfigError("down".to_string()));
+//!
+//! assert_eq!(fake.generate_mint().await.unwrap().pubkey, mint);
+//! assert!(fake.generate_mint().await.is_err());
+//! assert_eq!(fake.calls_to(Endpoint::GenerateMint).len(), 2);
+//! # }
+//! ```
+
+use std::{
+    any::Any,
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+};
+
+use async_trait::async_trait;
+use solana_sdk::{pubkey::Pubkey, transaction::VersionedTransaction};
+
+use crate::{
+    api::{Endpoint, FuryApi},
+    sdk::{
+        AnalyticsPnlOptions, AnalyticsPnlResponse, BuyTokenRequest, BuyTokenResponse, Delivery,
+        FuryError, GenerateMintResponse, HealthCheckResponse, SellRequest, SellResponse,
+        SendOutcome, TokenBurnRequest, TokenBurnResponse, TokenCleanerRequest,
+        TokenCleanerResponse, TokenTransferRequest, TokenTransferResponse, TokensCreateRequest,
+        TokensCreateResponse, WalletsConsolidateRequest, WalletsConsolidateResponse,
+        WalletsDistributeRequest, WalletsDistributeResponse,
+    },
+};
+
+/// A call received by `FakeFuryApi`, with its arguments
+#[derive(Debug, Clone)]
+pub enum FakeCall {
+    HealthCheck,
+    BuyToken(BuyTokenRequest),
+    SellToken(SellRequest),
+    SendTransactions {
+        transactions: Vec<VersionedTransaction>,
+        delivery: Delivery,
+    },
+    TokenTransfer(TokenTransferRequest),
+    TokensCreate(TokensCreateRequest),
+    TokenBurn(TokenBurnRequest),
+    TokenCleaner(TokenCleanerRequest),
+    AnalyticsPnl {
+        addresses: Vec<Pubkey>,
+        token_address: Pubkey,
+        options: AnalyticsPnlOptions,
+    },
+    GenerateMint,
+    WalletsDistribute(WalletsDistributeRequest),
+    WalletsConsolidate(WalletsConsolidateRequest),
+}
+
+impl FakeCall {
+    pub fn endpoint(&self) -> Endpoint {
+        match self {
+            FakeCall::HealthCheck => Endpoint::HealthCheck,
+            FakeCall::BuyToken(_) => Endpoint::BuyToken,
+            FakeCall::SellToken(_) => Endpoint::SellToken,
+            FakeCall::SendTransactions { .. } => Endpoint::SendTransactions,
+            FakeCall::TokenTransfer(_) => Endpoint::TokenTransfer,
+            FakeCall::TokensCreate(_) => Endpoint::TokensCreate,
+            FakeCall::TokenBurn(_) => Endpoint::TokenBurn,
+            FakeCall::TokenCleaner(_) => Endpoint::TokenCleaner,
+            FakeCall::AnalyticsPnl { .. } => Endpoint::AnalyticsPnl,
+            FakeCall::GenerateMint => Endpoint::GenerateMint,
+            FakeCall::WalletsDistribute(_) => Endpoint::WalletsDistribute,
+            FakeCall::WalletsConsolidate(_) => Endpoint::WalletsConsolidate,
+        }
+    }
+}
+
+/// Response type of an endpoint, which lets `FakeFuryApi::push_response`
+/// queue it without naming the endpoint
+pub trait FakeResponse: Send + 'static {
+    const ENDPOINT: Endpoint;
+}
+
+macro_rules! fake_response {
+    ($($response:ty => $endpoint:ident),* $(,)?) => {
+        $(impl FakeResponse for $response {
+            const ENDPOINT: Endpoint = Endpoint::$endpoint;
+        })*
+    };
+}
+
+fake_response! {
+    HealthCheckResponse => HealthCheck,
+    BuyTokenResponse => BuyToken,
+    SellResponse => SellToken,
+    SendOutcome => SendTransactions,
+    TokenTransferResponse => TokenTransfer,
+    TokensCreateResponse => TokensCreate,
+    TokenBurnResponse => TokenBurn,
+    TokenCleanerResponse => TokenCleaner,
+    AnalyticsPnlResponse => AnalyticsPnl,
+    GenerateMintResponse => GenerateMint,
+    WalletsDistributeResponse => WalletsDistribute,
+    WalletsConsolidateResponse => WalletsConsolidate,
+}
+
+enum Scripted {
+    Response(Box<dyn Any + Send>),
+    Error(FuryError),
+}
+
+#[derive(Default)]
+struct State {
+    scripted: HashMap<Endpoint, VecDeque<Scripted>>,
+    calls: Vec<FakeCall>,
+}
+
+/// `FuryApi` backed by queues of scripted responses, one per endpoint.
+///
+/// Each call records its arguments and pops the next response of its
+/// endpoint. A call with nothing queued fails with `FuryError::Other`.
+#[derive(Default)]
+pub struct FakeFuryApi {
+    state: Mutex<State>,
+}
+
+impl FakeFuryApi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a successful response for the endpoint it belongs to
+    pub fn push_response<R: FakeResponse>(&self, response: R) {
+        self.push(R::ENDPOINT, Scripted::Response(Box::new(response)));
+    }
+
+    /// Queues a failure for `endpoint`
+    pub fn push_error(&self, endpoint: Endpoint, error: FuryError) {
+        self.push(endpoint, Scripted::Error(error));
+    }
+
+    /// Every call received so far, in order
+    pub fn calls(&self) -> Vec<FakeCall> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    pub fn calls_to(&self, endpoint: Endpoint) -> Vec<FakeCall> {
+        self.state
+            .lock()
+            .unwrap()
+            .calls
+            .iter()
+            .filter(|call| call.endpoint() == endpoint)
+            .cloned()
+            .collect()
+    }
+
+    /// Scripted responses not consumed yet for `endpoint`
+    pub fn remaining(&self, endpoint: Endpoint) -> usize {
+        self.state
+            .lock()
+            .unwrap()
+            .scripted
+            .get(&endpoint)
+            .map_or(0, VecDeque::len)
+    }
+
+    fn push(&self, endpoint: Endpoint, scripted: Scripted) {
+        self.state
+            .lock()
+            .unwrap()
+            .scripted
+            .entry(endpoint)
+            .or_default()
+            .push_back(scripted);
+    }
+
+    fn respond<R: FakeResponse>(&self, call: FakeCall) -> Result<R, FuryError> {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(call);
+        let scripted = state
+            .scripted
+            .get_mut(&R::ENDPOINT)
+            .and_then(VecDeque::pop_front);
+        match scripted {
+            // Responses are queued under their own type's endpoint, so the
+            // downcast cannot fail
+            Some(Scripted::Response(response)) => Ok(*response
+                .downcast::<R>()
+                .expect("scripted response matches its endpoint")),
+            Some(Scripted::Error(error)) => Err(error),
+            None => Err(FuryError::Other(anyhow::anyhow!(
+                "No scripted response for {}",
+                R::ENDPOINT
+            ))),
+        }
+    }
+}
+
+#[async_trait]
+impl FuryApi for FakeFuryApi {
+    async fn health_check(&self) -> Result<HealthCheckResponse, FuryError> {
+        self.respond(FakeCall::HealthCheck)
+    }
+
+    async fn buy_token(&self, data: &BuyTokenRequest) -> Result<BuyTokenResponse, FuryError> {
+        self.respond(FakeCall::BuyToken(data.clone()))
+    }
+
+    async fn sell_token(&self, data: &SellRequest) -> Result<SellResponse, FuryError> {
+        self.respond(FakeCall::SellToken(data.clone()))
+    }
+
+    async fn send_transactions(
+        &self,
+        transactions: &[VersionedTransaction],
+        delivery: Delivery,
+    ) -> Result<SendOutcome, FuryError> {
+        self.respond(FakeCall::SendTransactions {
+            transactions: transactions.to_vec(),
+            delivery,
+        })
+    }
+
+    async fn token_transfer(
+        &self,
+        data: &TokenTransferRequest,
+    ) -> Result<TokenTransferResponse, FuryError> {
+        self.respond(FakeCall::TokenTransfer(data.clone()))
+    }
+
+    async fn tokens_create(
+        &self,
+        data: &TokensCreateRequest,
+    ) -> Result<TokensCreateResponse, FuryError> {
+        self.respond(FakeCall::TokensCreate(data.clone()))
+    }
+
+    async fn token_burn(&self, data: &TokenBurnRequest) -> Result<TokenBurnResponse, FuryError> {
+        self.respond(FakeCall::TokenBurn(data.clone()))
+    }
+
+    async fn token_cleaner(
+        &self,
+        data: &TokenCleanerRequest,
+    ) -> Result<TokenCleanerResponse, FuryError> {
+        self.respond(FakeCall::TokenCleaner(data.clone()))
+    }
+
+    async fn analytics_pnl(
+        &self,
+        addresses: &[Pubkey],
+        token_address: Pubkey,
+        options: AnalyticsPnlOptions,
+    ) -> Result<AnalyticsPnlResponse, FuryError> {
+        self.respond(FakeCall::AnalyticsPnl {
+            addresses: addresses.to_vec(),
+            token_address,
+            options,
+        })
+    }
+
+    async fn generate_mint(&self) -> Result<GenerateMintResponse, FuryError> {
+        self.respond(FakeCall::GenerateMint)
+    }
+
+    async fn wallets_distribute(
+        &self,
+        data: &WalletsDistributeRequest,
+    ) -> Result<WalletsDistributeResponse, FuryError> {
+        self.respond(FakeCall::WalletsDistribute(data.clone()))
+    }
+
+    async fn wallets_consolidate(
+        &self,
+        data: &WalletsConsolidateRequest,
+    ) -> Result<WalletsConsolidateResponse, FuryError> {
+        self.respond(FakeCall::WalletsConsolidate(data.clone()))
+    }
+}
@@ -0,0 +1,1562 @@
+//! Decodes transactions into a structured summary, for audit logs and for
+//! showing operators what they are about to sign.
+//!
+//! ```
+//! # use fury_sdk::inspect::inspect_base58;
+//! # fn show(serialized_tx: &str) -> Result<(), fury_sdk::utils::SignError> {
+//! let summary = inspect_base58(serialized_tx)?;
+//! println!("{}", summary);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt::{self, Display};
+
+use serde::{Serialize, Serializer};
+use solana_sdk::{
+    hash::Hash,
+    instruction::CompiledInstruction,
+    pubkey,
+    pubkey::Pubkey,
+    sanitize::SanitizeError,
+    transaction::{TransactionVersion, VersionedTransaction},
+};
+use solana_system_interface::{instruction::SystemInstruction, program as system_program};
+
+use crate::{
+    amount::{Lamports, TokenAmount},
+    serde_utils::{display_fromstr, display_fromstr_option, display_fromstr_vec},
+    transactions::{required_signers, UnsignedTransactions},
+    utils::{deserialize_transaction, SignError},
+};
+
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    pubkey!("ComputeBudget111111111111111111111111111111");
+pub const NATIVE_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
+
+/// An account referenced by a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccountKey {
+    /// Listed in the message itself
+    Static(Pubkey),
+    /// Loaded at execution time from entry `index` of an address lookup
+    /// table, so the address is not known offline
+    Lookup { table: Pubkey, index: u8 },
+}
+
+impl AccountKey {
+    /// The address, if it is listed in the message
+    pub fn pubkey(&self) -> Option<&Pubkey> {
+        match self {
+            AccountKey::Static(pubkey) => Some(pubkey),
+            AccountKey::Lookup { .. } => None,
+        }
+    }
+}
+
+impl Display for AccountKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountKey::Static(pubkey) => write!(f, "{}", pubkey),
+            AccountKey::Lookup { table, index } => write!(f, "{}[{}]", table, index),
+        }
+    }
+}
+
+impl Serialize for AccountKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountSummary {
+    pub key: AccountKey,
+    pub signer: bool,
+    /// Whether the runtime will lock the account for writing
+    pub writable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LookupTableSummary {
+    #[serde(with = "display_fromstr")]
+    pub account_key: Pubkey,
+    pub writable_indexes: Vec<u8>,
+    pub readonly_indexes: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstructionSummary {
+    #[serde(with = "display_fromstr")]
+    pub program_id: Pubkey,
+    /// Name of a well-known program
+    pub program_name: Option<&'static str>,
+    pub accounts: Vec<AccountKey>,
+    pub decoded: DecodedInstruction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionSummary {
+    pub version: TransactionVersion,
+    #[serde(with = "display_fromstr")]
+    pub recent_blockhash: Hash,
+    #[serde(serialize_with = "display_fromstr_vec::serialize")]
+    pub signers: Vec<Pubkey>,
+    /// Every account in the order the message indexes them: static keys,
+    /// then writable and readonly lookup table entries
+    pub accounts: Vec<AccountSummary>,
+    /// Invoked programs in order of first use
+    #[serde(serialize_with = "display_fromstr_vec::serialize")]
+    pub programs: Vec<Pubkey>,
+    pub instructions: Vec<InstructionSummary>,
+    pub lookup_tables: Vec<LookupTableSummary>,
+}
+
+/// A top-level instruction decoded according to its program
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "program", content = "instruction", rename_all = "camelCase")]
+pub enum DecodedInstruction {
+    System(SystemInstructionSummary),
+    /// SPL Token or Token-2022
+    Token(TokenInstructionSummary),
+    ComputeBudget(ComputeBudgetInstructionSummary),
+    AssociatedToken(AssociatedTokenInstructionSummary),
+    /// A program this module does not decode, or data it could not parse
+    Unknown {
+        #[serde(serialize_with = "serialize_base58")]
+        data: Vec<u8>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum SystemInstructionSummary {
+    CreateAccount {
+        from: AccountKey,
+        to: AccountKey,
+        #[serde(serialize_with = "serialize_lamports")]
+        lamports: Lamports,
+        space: u64,
+        #[serde(with = "display_fromstr")]
+        owner: Pubkey,
+    },
+    CreateAccountWithSeed {
+        from: AccountKey,
+        to: AccountKey,
+        #[serde(with = "display_fromstr")]
+        base: Pubkey,
+        seed: String,
+        #[serde(serialize_with = "serialize_lamports")]
+        lamports: Lamports,
+        space: u64,
+        #[serde(with = "display_fromstr")]
+        owner: Pubkey,
+    },
+    Assign {
+        account: AccountKey,
+        #[serde(with = "display_fromstr")]
+        owner: Pubkey,
+    },
+    Transfer {
+        from: AccountKey,
+        to: AccountKey,
+        #[serde(serialize_with = "serialize_lamports")]
+        lamports: Lamports,
+    },
+    TransferWithSeed {
+        /// Account derived from `base`, which the SOL leaves
+        from: AccountKey,
+        /// Signer authorizing the transfer
+        base: AccountKey,
+        to: AccountKey,
+        #[serde(serialize_with = "serialize_lamports")]
+        lamports: Lamports,
+    },
+    Allocate {
+        account: AccountKey,
+        space: u64,
+    },
+    /// Nonce and other rarely used instructions, by name
+    Other {
+        name: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum TokenInstructionSummary {
+    InitializeMint {
+        mint: AccountKey,
+        decimals: u8,
+        #[serde(with = "display_fromstr")]
+        mint_authority: Pubkey,
+        #[serde(serialize_with = "display_fromstr_option::serialize")]
+        freeze_authority: Option<Pubkey>,
+    },
+    InitializeAccount {
+        account: AccountKey,
+        mint: AccountKey,
+        owner: AccountKey,
+    },
+    Transfer {
+        source: AccountKey,
+        destination: AccountKey,
+        authority: AccountKey,
+        amount: u64,
+    },
+    TransferChecked {
+        source: AccountKey,
+        mint: AccountKey,
+        destination: AccountKey,
+        authority: AccountKey,
+        amount: TokenAmount,
+    },
+    Approve {
+        source: AccountKey,
+        delegate: AccountKey,
+        owner: AccountKey,
+        amount: u64,
+    },
+    Revoke {
+        source: AccountKey,
+        owner: AccountKey,
+    },
+    SetAuthority {
+        account: AccountKey,
+        authority_type: u8,
+        #[serde(serialize_with = "display_fromstr_option::serialize")]
+        new_authority: Option<Pubkey>,
+    },
+    MintTo {
+        mint: AccountKey,
+        account: AccountKey,
+        amount: u64,
+    },
+    Burn {
+        account: AccountKey,
+        mint: AccountKey,
+        amount: u64,
+    },
+    CloseAccount {
+        account: AccountKey,
+        destination: AccountKey,
+        owner: AccountKey,
+    },
+    SyncNative {
+        account: AccountKey,
+    },
+    /// Instructions not decoded in detail, by tag
+    Other {
+        tag: u8,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ComputeBudgetInstructionSummary {
+    RequestHeapFrame { bytes: u32 },
+    SetComputeUnitLimit { units: u32 },
+    SetComputeUnitPrice { micro_lamports: u64 },
+    SetLoadedAccountsDataSizeLimit { bytes: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum AssociatedTokenInstructionSummary {
+    Create {
+        funder: AccountKey,
+        account: AccountKey,
+        wallet: AccountKey,
+        mint: AccountKey,
+        idempotent: bool,
+    },
+    RecoverNested {
+        nested_account: AccountKey,
+        destination: AccountKey,
+        wallet: AccountKey,
+    },
+}
+
+/// Decodes a single base58 encoded transaction, such as
+/// `TokenBurnData::transaction`
+pub fn inspect_base58(serialized_tx: &str) -> Result<TransactionSummary, SignError> {
+    let transaction = deserialize_transaction(0, serialized_tx)?;
+    inspect_transaction(&transaction).map_err(|source| SignError::Malformed { index: 0, source })
+}
+
+/// Decodes a batch of base58 encoded transactions
+pub fn inspect_transactions<S: AsRef<str>>(
+    serialized_txs: &[S],
+) -> Result<Vec<TransactionSummary>, SignError> {
+    UnsignedTransactions::from_base58(serialized_txs)?.inspect()
+}
+
+/// Builds the summary of a transaction. Fails if the message references
+/// accounts it does not contain.
+pub fn inspect_transaction(
+    transaction: &VersionedTransaction,
+) -> Result<TransactionSummary, SanitizeError> {
+    let message = &transaction.message;
+    message.sanitize()?;
+
+    let lookups = message.address_table_lookups().unwrap_or_default();
+    let keys: Vec<AccountKey> = message
+        .static_account_keys()
+        .iter()
+        .map(|key| AccountKey::Static(*key))
+        .chain(lookups.iter().flat_map(|lookup| {
+            lookup
+                .writable_indexes
+                .iter()
+                .map(|index| AccountKey::Lookup {
+                    table: lookup.account_key,
+                    index: *index,
+                })
+        }))
+        .chain(lookups.iter().flat_map(|lookup| {
+            lookup
+                .readonly_indexes
+                .iter()
+                .map(|index| AccountKey::Lookup {
+                    table: lookup.account_key,
+                    index: *index,
+                })
+        }))
+        .collect();
+
+    let accounts = keys
+        .iter()
+        .enumerate()
+        .map(|(index, key)| AccountSummary {
+            key: *key,
+            signer: message.is_signer(index),
+            writable: message.is_maybe_writable(index, None),
+        })
+        .collect();
+
+    let mut programs = Vec::new();
+    let instructions = message
+        .instructions()
+        .iter()
+        .map(|instruction| {
+            let program_id = *instruction.program_id(message.static_account_keys());
+            if !programs.contains(&program_id) {
+                programs.push(program_id);
+            }
+            let accounts: Vec<AccountKey> = instruction
+                .accounts
+                .iter()
+                .map(|index| keys[*index as usize])
+                .collect();
+            InstructionSummary {
+                program_id,
+                program_name: program_name(&program_id),
+                decoded: decode_instruction(&program_id, &accounts, instruction),
+                accounts,
+            }
+        })
+        .collect();
+
+    Ok(TransactionSummary {
+        version: transaction.version(),
+        recent_blockhash: *message.recent_blockhash(),
+        signers: required_signers(transaction).to_vec(),
+        accounts,
+        programs,
+        instructions,
+        lookup_tables: lookups
+            .iter()
+            .map(|lookup| LookupTableSummary {
+                account_key: lookup.account_key,
+                writable_indexes: lookup.writable_indexes.clone(),
+                readonly_indexes: lookup.readonly_indexes.clone(),
+            })
+            .collect(),
+    })
+}
+
+/// Name of a program this module knows how to decode
+pub fn program_name(program_id: &Pubkey) -> Option<&'static str> {
+    match *program_id {
+        system_program::ID => Some("System Program"),
+        TOKEN_PROGRAM_ID => Some("Token Program"),
+        TOKEN_2022_PROGRAM_ID => Some("Token-2022 Program"),
+        ASSOCIATED_TOKEN_PROGRAM_ID => Some("Associated Token Program"),
+        COMPUTE_BUDGET_PROGRAM_ID => Some("Compute Budget Program"),
+        _ => None,
+    }
+}
+
+/// Associated token account of `owner` for `mint`
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+fn decode_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountKey],
+    instruction: &CompiledInstruction,
+) -> DecodedInstruction {
+    let data = &instruction.data;
+    let decoded = match *program_id {
+        system_program::ID => {
+            decode_system_instruction(accounts, data).map(DecodedInstruction::System)
+        }
+        TOKEN_PROGRAM_ID | TOKEN_2022_PROGRAM_ID => {
+            decode_token_instruction(accounts, data).map(DecodedInstruction::Token)
+        }
+        COMPUTE_BUDGET_PROGRAM_ID => {
+            decode_compute_budget_instruction(data).map(DecodedInstruction::ComputeBudget)
+        }
+        ASSOCIATED_TOKEN_PROGRAM_ID => decode_associated_token_instruction(accounts, data)
+            .map(DecodedInstruction::AssociatedToken),
+        _ => None,
+    };
+    decoded.unwrap_or_else(|| DecodedInstruction::Unknown { data: data.clone() })
+}
+
+fn decode_system_instruction(
+    accounts: &[AccountKey],
+    data: &[u8],
+) -> Option<SystemInstructionSummary> {
+    let account = |index: usize| accounts.get(index).copied();
+    Some(
+        match bincode::deserialize::<SystemInstruction>(data).ok()? {
+            SystemInstruction::CreateAccount {
+                lamports,
+                space,
+                owner,
+            } => SystemInstructionSummary::CreateAccount {
+                from: account(0)?,
+                to: account(1)?,
+                lamports: Lamports::new(lamports),
+                space,
+                owner,
+            },
+            SystemInstruction::CreateAccountWithSeed {
+                base,
+                seed,
+                lamports,
+                space,
+                owner,
+            } => SystemInstructionSummary::CreateAccountWithSeed {
+                from: account(0)?,
+                to: account(1)?,
+                base,
+                seed,
+                lamports: Lamports::new(lamports),
+                space,
+                owner,
+            },
+            SystemInstruction::Assign { owner } => SystemInstructionSummary::Assign {
+                account: account(0)?,
+                owner,
+            },
+            SystemInstruction::Transfer { lamports } => SystemInstructionSummary::Transfer {
+                from: account(0)?,
+                to: account(1)?,
+                lamports: Lamports::new(lamports),
+            },
+            SystemInstruction::TransferWithSeed { lamports, .. } => {
+                SystemInstructionSummary::TransferWithSeed {
+                    from: account(0)?,
+                    base: account(1)?,
+                    to: account(2)?,
+                    lamports: Lamports::new(lamports),
+                }
+            }
+            SystemInstruction::Allocate { space } => SystemInstructionSummary::Allocate {
+                account: account(0)?,
+                space,
+            },
+            other => {
+                let name = format!("{:?}", other);
+                let name = name
+                    .split(|c: char| !c.is_alphanumeric())
+                    .next()
+                    .unwrap_or_default();
+                SystemInstructionSummary::Other {
+                    name: name.to_string(),
+                }
+            }
+        },
+    )
+}
+
+// SPL Token instruction tags, shared by Token-2022
+const TOKEN_INITIALIZE_MINT: u8 = 0;
+const TOKEN_INITIALIZE_ACCOUNT: u8 = 1;
+const TOKEN_TRANSFER: u8 = 3;
+const TOKEN_APPROVE: u8 = 4;
+const TOKEN_REVOKE: u8 = 5;
+const TOKEN_SET_AUTHORITY: u8 = 6;
+const TOKEN_MINT_TO: u8 = 7;
+const TOKEN_BURN: u8 = 8;
+const TOKEN_CLOSE_ACCOUNT: u8 = 9;
+const TOKEN_TRANSFER_CHECKED: u8 = 12;
+const TOKEN_APPROVE_CHECKED: u8 = 13;
+const TOKEN_MINT_TO_CHECKED: u8 = 14;
+const TOKEN_BURN_CHECKED: u8 = 15;
+const TOKEN_INITIALIZE_ACCOUNT2: u8 = 16;
+const TOKEN_SYNC_NATIVE: u8 = 17;
+const TOKEN_INITIALIZE_ACCOUNT3: u8 = 18;
+const TOKEN_INITIALIZE_MINT2: u8 = 20;
+
+fn decode_token_instruction(
+    accounts: &[AccountKey],
+    data: &[u8],
+) -> Option<TokenInstructionSummary> {
+    let account = |index: usize| accounts.get(index).copied();
+    let (&tag, rest) = data.split_first()?;
+    Some(match tag {
+        TOKEN_INITIALIZE_MINT | TOKEN_INITIALIZE_MINT2 => {
+            let (&decimals, rest) = rest.split_first()?;
+            let (mint_authority, rest) = read_pubkey(rest)?;
+            let (freeze_authority, _) = read_optional_pubkey(rest)?;
+            TokenInstructionSummary::InitializeMint {
+                mint: account(0)?,
+                decimals,
+                mint_authority,
+                freeze_authority,
+            }
+        }
+        TOKEN_INITIALIZE_ACCOUNT => TokenInstructionSummary::InitializeAccount {
+            account: account(0)?,
+            mint: account(1)?,
+            owner: account(2)?,
+        },
+        TOKEN_INITIALIZE_ACCOUNT2 | TOKEN_INITIALIZE_ACCOUNT3 => {
+            TokenInstructionSummary::InitializeAccount {
+                account: account(0)?,
+                mint: account(1)?,
+                owner: AccountKey::Static(read_pubkey(rest)?.0),
+            }
+        }
+        TOKEN_TRANSFER => TokenInstructionSummary::Transfer {
+            source: account(0)?,
+            destination: account(1)?,
+            authority: account(2)?,
+            amount: read_u64(rest)?,
+        },
+        TOKEN_TRANSFER_CHECKED => TokenInstructionSummary::TransferChecked {
+            source: account(0)?,
+            mint: account(1)?,
+            destination: account(2)?,
+            authority: account(3)?,
+            // Kept as sent, even if the decimals are out of range
+            amount: TokenAmount {
+                raw: read_u64(rest)?,
+                decimals: *rest.get(8)?,
+            },
+        },
+        TOKEN_APPROVE => TokenInstructionSummary::Approve {
+            source: account(0)?,
+            delegate: account(1)?,
+            owner: account(2)?,
+            amount: read_u64(rest)?,
+        },
+        TOKEN_APPROVE_CHECKED => TokenInstructionSummary::Approve {
+            source: account(0)?,
+            delegate: account(2)?,
+            owner: account(3)?,
+            amount: read_u64(rest)?,
+        },
+        TOKEN_REVOKE => TokenInstructionSummary::Revoke {
+            source: account(0)?,
+            owner: account(1)?,
+        },
+        TOKEN_SET_AUTHORITY => {
+            let (&authority_type, rest) = rest.split_first()?;
+            TokenInstructionSummary::SetAuthority {
+                account: account(0)?,
+                authority_type,
+                new_authority: read_optional_pubkey(rest)?.0,
+            }
+        }
+        TOKEN_MINT_TO | TOKEN_MINT_TO_CHECKED => TokenInstructionSummary::MintTo {
+            mint: account(0)?,
+            account: account(1)?,
+            amount: read_u64(rest)?,
+        },
+        TOKEN_BURN | TOKEN_BURN_CHECKED => TokenInstructionSummary::Burn {
+            account: account(0)?,
+            mint: account(1)?,
+            amount: read_u64(rest)?,
+        },
+        TOKEN_CLOSE_ACCOUNT => TokenInstructionSummary::CloseAccount {
+            account: account(0)?,
+            destination: account(1)?,
+            owner: account(2)?,
+        },
+        TOKEN_SYNC_NATIVE => TokenInstructionSummary::SyncNative {
+            account: account(0)?,
+        },
+        tag => TokenInstructionSummary::Other { tag },
+    })
+}
+
+fn decode_compute_budget_instruction(data: &[u8]) -> Option<ComputeBudgetInstructionSummary> {
+    let (&tag, rest) = data.split_first()?;
+    Some(match tag {
+        1 => ComputeBudgetInstructionSummary::RequestHeapFrame {
+            bytes: read_u32(rest)?,
+        },
+        2 => ComputeBudgetInstructionSummary::SetComputeUnitLimit {
+            units: read_u32(rest)?,
+        },
+        3 => ComputeBudgetInstructionSummary::SetComputeUnitPrice {
+            micro_lamports: read_u64(rest)?,
+        },
+        4 => ComputeBudgetInstructionSummary::SetLoadedAccountsDataSizeLimit {
+            bytes: read_u32(rest)?,
+        },
+        _ => return None,
+    })
+}
+
+fn decode_associated_token_instruction(
+    accounts: &[AccountKey],
+    data: &[u8],
+) -> Option<AssociatedTokenInstructionSummary> {
+    let account = |index: usize| accounts.get(index).copied();
+    Some(match data.first() {
+        None | Some(0) | Some(1) => AssociatedTokenInstructionSummary::Create {
+            funder: account(0)?,
+            account: account(1)?,
+            wallet: account(2)?,
+            mint: account(3)?,
+            idempotent: data.first() == Some(&1),
+        },
+        Some(2) => AssociatedTokenInstructionSummary::RecoverNested {
+            nested_account: account(0)?,
+            destination: account(2)?,
+            wallet: account(5)?,
+        },
+        _ => return None,
+    })
+}
+
+fn read_u32(data: &[u8]) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(..4)?.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8]) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(..8)?.try_into().ok()?))
+}
+
+fn read_pubkey(data: &[u8]) -> Option<(Pubkey, &[u8])> {
+    let key = Pubkey::try_from(data.get(..32)?).ok()?;
+    Some((key, &data[32..]))
+}
+
+/// Reads an SPL Token `COption<Pubkey>`: a one byte flag followed by the key
+fn read_optional_pubkey(data: &[u8]) -> Option<(Option<Pubkey>, &[u8])> {
+    let (&flag, rest) = data.split_first()?;
+    match flag {
+        0 => Some((None, rest)),
+        1 => read_pubkey(rest).map(|(key, rest)| (Some(key), rest)),
+        _ => None,
+    }
+}
+
+/// Audit logs want the exact integer rather than the SOL number the API uses
+fn serialize_lamports<S: Serializer>(
+    lamports: &Lamports,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(lamports.lamports())
+}
+
+fn serialize_base58<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&bs58::encode(data).into_string())
+}
+
+impl Display for TransactionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let version = match self.version {
+            TransactionVersion::Legacy(_) => "legacy".to_string(),
+            TransactionVersion::Number(version) => format!("v{}", version),
+        };
+        writeln!(
+            f,
+            "Transaction ({}), blockhash {}",
+            version, self.recent_blockhash
+        )?;
+        writeln!(f, "Accounts:")?;
+        for (index, account) in self.accounts.iter().enumerate() {
+            let mut flags = Vec::new();
+            if account.signer {
+                flags.push("signer");
+            }
+            if account.writable {
+                flags.push("writable");
+            }
+            writeln!(f, "  {:>2} {} [{}]", index, account.key, flags.join(", "))?;
+        }
+        writeln!(f, "Instructions:")?;
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            let program = instruction
+                .program_name
+                .map(str::to_string)
+                .unwrap_or_else(|| instruction.program_id.to_string());
+            writeln!(f, "  {:>2} {}: {}", index, program, instruction.decoded)?;
+        }
+        for table in &self.lookup_tables {
+            writeln!(
+                f,
+                "Lookup table {}: writable {:?}, readonly {:?}",
+                table.account_key, table.writable_indexes, table.readonly_indexes
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for DecodedInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodedInstruction::System(instruction) => instruction.fmt(f),
+            DecodedInstruction::Token(instruction) => instruction.fmt(f),
+            DecodedInstruction::ComputeBudget(instruction) => instruction.fmt(f),
+            DecodedInstruction::AssociatedToken(instruction) => instruction.fmt(f),
+            DecodedInstruction::Unknown { data } => {
+                write!(f, "data {}", bs58::encode(data).into_string())
+            }
+        }
+    }
+}
+
+impl Display for SystemInstructionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SystemInstructionSummary::CreateAccount {
+                from,
+                to,
+                lamports,
+                space,
+                owner,
+            }
+            | SystemInstructionSummary::CreateAccountWithSeed {
+                from,
+                to,
+                lamports,
+                space,
+                owner,
+                ..
+            } => write!(
+                f,
+                "create account {} funded with {} by {}, {} bytes owned by {}",
+                to, lamports, from, space, owner
+            ),
+            SystemInstructionSummary::Assign { account, owner } => {
+                write!(f, "assign {} to {}", account, owner)
+            }
+            SystemInstructionSummary::Transfer { from, to, lamports }
+            | SystemInstructionSummary::TransferWithSeed {
+                from, to, lamports, ..
+            } => {
+                write!(f, "transfer {} from {} to {}", lamports, from, to)
+            }
+            SystemInstructionSummary::Allocate { account, space } => {
+                write!(f, "allocate {} bytes for {}", space, account)
+            }
+            SystemInstructionSummary::Other { name } => f.write_str(name),
+        }
+    }
+}
+
+impl Display for TokenInstructionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenInstructionSummary::InitializeMint {
+                mint,
+                decimals,
+                mint_authority,
+                freeze_authority,
+            } => write!(
+                f,
+                "initialize mint {} with {} decimals, mint authority {}, freeze authority {}",
+                mint,
+                decimals,
+                mint_authority,
+                freeze_authority
+                    .map(|key| key.to_string())
+                    .unwrap_or_else(|| "none".to_string())
+            ),
+            TokenInstructionSummary::InitializeAccount {
+                account,
+                mint,
+                owner,
+            } => write!(
+                f,
+                "initialize token account {} for mint {} owned by {}",
+                account, mint, owner
+            ),
+            TokenInstructionSummary::Transfer {
+                source,
+                destination,
+                authority,
+                amount,
+            } => write!(
+                f,
+                "transfer {} raw units from {} to {}, authority {}",
+                amount, source, destination, authority
+            ),
+            TokenInstructionSummary::TransferChecked {
+                source,
+                mint,
+                destination,
+                authority,
+                amount,
+            } => write!(
+                f,
+                "transfer {} of mint {} from {} to {}, authority {}",
+                amount, mint, source, destination, authority
+            ),
+            TokenInstructionSummary::Approve {
+                source,
+                delegate,
+                owner,
+                amount,
+            } => write!(
+                f,
+                "approve {} to spend {} raw units from {}, owner {}",
+                delegate, amount, source, owner
+            ),
+            TokenInstructionSummary::Revoke { source, owner } => {
+                write!(f, "revoke delegate of {}, owner {}", source, owner)
+            }
+            TokenInstructionSummary::SetAuthority {
+                account,
+                authority_type,
+                new_authority,
+            } => write!(
+                f,
+                "set authority type {} of {} to {}",
+                authority_type,
+                account,
+                new_authority
+                    .map(|key| key.to_string())
+                    .unwrap_or_else(|| "none".to_string())
+            ),
+            TokenInstructionSummary::MintTo {
+                mint,
+                account,
+                amount,
+            } => write!(f, "mint {} raw units of {} to {}", amount, mint, account),
+            TokenInstructionSummary::Burn {
+                account,
+                mint,
+                amount,
+            } => write!(f, "burn {} raw units of {} from {}", amount, mint, account),
+            TokenInstructionSummary::CloseAccount {
+                account,
+                destination,
+                owner,
+            } => write!(
+                f,
+                "close {} sending rent to {}, owner {}",
+                account, destination, owner
+            ),
+            TokenInstructionSummary::SyncNative { account } => {
+                write!(f, "sync native balance of {}", account)
+            }
+            TokenInstructionSummary::Other { tag } => write!(f, "instruction {}", tag),
+        }
+    }
+}
+
+impl Display for ComputeBudgetInstructionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComputeBudgetInstructionSummary::RequestHeapFrame { bytes } => {
+                write!(f, "request heap frame of {} bytes", bytes)
+            }
+            ComputeBudgetInstructionSummary::SetComputeUnitLimit { units } => {
+                write!(f, "set compute unit limit to {}", units)
+            }
+            ComputeBudgetInstructionSummary::SetComputeUnitPrice { micro_lamports } => {
+                write!(
+                    f,
+                    "set compute unit price to {} micro-lamports",
+                    micro_lamports
+                )
+            }
+            ComputeBudgetInstructionSummary::SetLoadedAccountsDataSizeLimit { bytes } => {
+                write!(f, "set loaded accounts data size limit to {} bytes", bytes)
+            }
+        }
+    }
+}
+
+impl Display for AssociatedTokenInstructionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssociatedTokenInstructionSummary::Create {
+                funder,
+                account,
+                wallet,
+                mint,
+                idempotent,
+            } => write!(
+                f,
+                "create{} token account {} for wallet {} and mint {}, funded by {}",
+                if *idempotent { " (idempotent)" } else { "" },
+                account,
+                wallet,
+                mint,
+                funder
+            ),
+            AssociatedTokenInstructionSummary::RecoverNested {
+                nested_account,
+                destination,
+                wallet,
+            } => write!(
+                f,
+                "recover nested account {} to {} for wallet {}",
+                nested_account, destination, wallet
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_compute_budget_interface::ComputeBudgetInstruction;
+    use solana_sdk::instruction::Instruction;
+    use solana_system_interface::instruction as system_instruction;
+    use spl_associated_token_account_client::{
+        address::get_associated_token_address_with_program_id,
+        instruction as associated_token_instruction,
+    };
+    use spl_token::instruction::{self as token_instruction, AuthorityType};
+
+    use super::*;
+    use crate::test_support::unsigned_transaction;
+
+    fn decode(instruction: Instruction) -> DecodedInstruction {
+        let payer = Pubkey::new_unique();
+        let transaction = unsigned_transaction(&payer, &[instruction]);
+        inspect_transaction(&transaction)
+            .unwrap()
+            .instructions
+            .remove(0)
+            .decoded
+    }
+
+    fn key(pubkey: &Pubkey) -> AccountKey {
+        AccountKey::Static(*pubkey)
+    }
+
+    fn token(summary: TokenInstructionSummary) -> DecodedInstruction {
+        DecodedInstruction::Token(summary)
+    }
+
+    #[test]
+    fn decodes_system_instructions() {
+        let (from, to, base, owner) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+
+        assert_eq!(
+            decode(system_instruction::create_account(
+                &from, &to, 5, 165, &owner
+            )),
+            DecodedInstruction::System(SystemInstructionSummary::CreateAccount {
+                from: key(&from),
+                to: key(&to),
+                lamports: Lamports::new(5),
+                space: 165,
+                owner,
+            })
+        );
+        assert_eq!(
+            decode(system_instruction::create_account_with_seed(
+                &from, &to, &base, "seed", 5, 165, &owner
+            )),
+            DecodedInstruction::System(SystemInstructionSummary::CreateAccountWithSeed {
+                from: key(&from),
+                to: key(&to),
+                base,
+                seed: "seed".to_string(),
+                lamports: Lamports::new(5),
+                space: 165,
+                owner,
+            })
+        );
+        assert_eq!(
+            decode(system_instruction::assign(&to, &owner)),
+            DecodedInstruction::System(SystemInstructionSummary::Assign {
+                account: key(&to),
+                owner,
+            })
+        );
+        assert_eq!(
+            decode(system_instruction::transfer(&from, &to, 7)),
+            DecodedInstruction::System(SystemInstructionSummary::Transfer {
+                from: key(&from),
+                to: key(&to),
+                lamports: Lamports::new(7),
+            })
+        );
+        assert_eq!(
+            decode(system_instruction::transfer_with_seed(
+                &from,
+                &base,
+                "seed".to_string(),
+                &owner,
+                &to,
+                7
+            )),
+            DecodedInstruction::System(SystemInstructionSummary::TransferWithSeed {
+                from: key(&from),
+                base: key(&base),
+                to: key(&to),
+                lamports: Lamports::new(7),
+            })
+        );
+        assert_eq!(
+            decode(system_instruction::allocate(&to, 165)),
+            DecodedInstruction::System(SystemInstructionSummary::Allocate {
+                account: key(&to),
+                space: 165,
+            })
+        );
+        assert_eq!(
+            decode(system_instruction::advance_nonce_account(&to, &from)),
+            DecodedInstruction::System(SystemInstructionSummary::Other {
+                name: "AdvanceNonceAccount".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn decodes_token_initialization() {
+        let (mint, account, owner) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let program = spl_token::id();
+
+        let initialize_mint = TokenInstructionSummary::InitializeMint {
+            mint: key(&mint),
+            decimals: 6,
+            mint_authority: owner,
+            freeze_authority: Some(account),
+        };
+        assert_eq!(
+            decode(
+                token_instruction::initialize_mint(&program, &mint, &owner, Some(&account), 6)
+                    .unwrap()
+            ),
+            token(initialize_mint.clone())
+        );
+        assert_eq!(
+            decode(
+                token_instruction::initialize_mint2(&program, &mint, &owner, Some(&account), 6)
+                    .unwrap()
+            ),
+            token(initialize_mint)
+        );
+        assert_eq!(
+            decode(token_instruction::initialize_mint2(&program, &mint, &owner, None, 9).unwrap()),
+            token(TokenInstructionSummary::InitializeMint {
+                mint: key(&mint),
+                decimals: 9,
+                mint_authority: owner,
+                freeze_authority: None,
+            })
+        );
+
+        let initialize_account = token(TokenInstructionSummary::InitializeAccount {
+            account: key(&account),
+            mint: key(&mint),
+            owner: key(&owner),
+        });
+        for instruction in [
+            token_instruction::initialize_account(&program, &account, &mint, &owner),
+            token_instruction::initialize_account2(&program, &account, &mint, &owner),
+            token_instruction::initialize_account3(&program, &account, &mint, &owner),
+        ] {
+            assert_eq!(decode(instruction.unwrap()), initialize_account);
+        }
+    }
+
+    #[test]
+    fn decodes_token_transfers() {
+        let (source, mint, destination, authority) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let program = spl_token::id();
+
+        assert_eq!(
+            decode(
+                token_instruction::transfer(&program, &source, &destination, &authority, &[], 42)
+                    .unwrap()
+            ),
+            token(TokenInstructionSummary::Transfer {
+                source: key(&source),
+                destination: key(&destination),
+                authority: key(&authority),
+                amount: 42,
+            })
+        );
+        assert_eq!(
+            decode(
+                token_instruction::transfer_checked(
+                    &program,
+                    &source,
+                    &mint,
+                    &destination,
+                    &authority,
+                    &[],
+                    42,
+                    6
+                )
+                .unwrap()
+            ),
+            token(TokenInstructionSummary::TransferChecked {
+                source: key(&source),
+                mint: key(&mint),
+                destination: key(&destination),
+                authority: key(&authority),
+                amount: TokenAmount {
+                    raw: 42,
+                    decimals: 6,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn decodes_token_delegation() {
+        let (source, mint, delegate, owner) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let program = spl_token::id();
+
+        let approve = token(TokenInstructionSummary::Approve {
+            source: key(&source),
+            delegate: key(&delegate),
+            owner: key(&owner),
+            amount: 42,
+        });
+        assert_eq!(
+            decode(
+                token_instruction::approve(&program, &source, &delegate, &owner, &[], 42).unwrap()
+            ),
+            approve
+        );
+        assert_eq!(
+            decode(
+                token_instruction::approve_checked(
+                    &program,
+                    &source,
+                    &mint,
+                    &delegate,
+                    &owner,
+                    &[],
+                    42,
+                    6
+                )
+                .unwrap()
+            ),
+            approve
+        );
+        assert_eq!(
+            decode(token_instruction::revoke(&program, &source, &owner, &[]).unwrap()),
+            token(TokenInstructionSummary::Revoke {
+                source: key(&source),
+                owner: key(&owner),
+            })
+        );
+    }
+
+    #[test]
+    fn decodes_token_set_authority() {
+        let (mint, owner, new_authority) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let program = spl_token::id();
+
+        assert_eq!(
+            decode(
+                token_instruction::set_authority(
+                    &program,
+                    &mint,
+                    Some(&new_authority),
+                    AuthorityType::FreezeAccount,
+                    &owner,
+                    &[]
+                )
+                .unwrap()
+            ),
+            token(TokenInstructionSummary::SetAuthority {
+                account: key(&mint),
+                authority_type: AuthorityType::FreezeAccount as u8,
+                new_authority: Some(new_authority),
+            })
+        );
+        assert_eq!(
+            decode(
+                token_instruction::set_authority(
+                    &program,
+                    &mint,
+                    None,
+                    AuthorityType::MintTokens,
+                    &owner,
+                    &[]
+                )
+                .unwrap()
+            ),
+            token(TokenInstructionSummary::SetAuthority {
+                account: key(&mint),
+                authority_type: AuthorityType::MintTokens as u8,
+                new_authority: None,
+            })
+        );
+    }
+
+    #[test]
+    fn decodes_token_supply_changes() {
+        let (mint, account, owner) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let program = spl_token::id();
+
+        let mint_to = token(TokenInstructionSummary::MintTo {
+            mint: key(&mint),
+            account: key(&account),
+            amount: 42,
+        });
+        assert_eq!(
+            decode(token_instruction::mint_to(&program, &mint, &account, &owner, &[], 42).unwrap()),
+            mint_to
+        );
+        assert_eq!(
+            decode(
+                token_instruction::mint_to_checked(&program, &mint, &account, &owner, &[], 42, 6)
+                    .unwrap()
+            ),
+            mint_to
+        );
+
+        let burn = token(TokenInstructionSummary::Burn {
+            account: key(&account),
+            mint: key(&mint),
+            amount: 42,
+        });
+        assert_eq!(
+            decode(token_instruction::burn(&program, &account, &mint, &owner, &[], 42).unwrap()),
+            burn
+        );
+        assert_eq!(
+            decode(
+                token_instruction::burn_checked(&program, &account, &mint, &owner, &[], 42, 6)
+                    .unwrap()
+            ),
+            burn
+        );
+    }
+
+    #[test]
+    fn decodes_token_account_management() {
+        let (account, destination, owner, mint) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let program = spl_token::id();
+
+        assert_eq!(
+            decode(
+                token_instruction::close_account(&program, &account, &destination, &owner, &[])
+                    .unwrap()
+            ),
+            token(TokenInstructionSummary::CloseAccount {
+                account: key(&account),
+                destination: key(&destination),
+                owner: key(&owner),
+            })
+        );
+        assert_eq!(
+            decode(token_instruction::sync_native(&program, &account).unwrap()),
+            token(TokenInstructionSummary::SyncNative {
+                account: key(&account),
+            })
+        );
+        assert_eq!(
+            decode(
+                token_instruction::freeze_account(&program, &account, &mint, &owner, &[]).unwrap()
+            ),
+            token(TokenInstructionSummary::Other { tag: 10 })
+        );
+    }
+
+    #[test]
+    fn decodes_token_2022_with_the_shared_layout() {
+        let (source, mint, destination, authority) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let instruction = spl_token_2022::instruction::transfer_checked(
+            &spl_token_2022::id(),
+            &source,
+            &mint,
+            &destination,
+            &authority,
+            &[],
+            42,
+            6,
+        )
+        .unwrap();
+        assert_eq!(instruction.program_id, TOKEN_2022_PROGRAM_ID);
+
+        assert_eq!(
+            decode(instruction),
+            token(TokenInstructionSummary::TransferChecked {
+                source: key(&source),
+                mint: key(&mint),
+                destination: key(&destination),
+                authority: key(&authority),
+                amount: TokenAmount {
+                    raw: 42,
+                    decimals: 6,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn decodes_compute_budget_instructions() {
+        let cases = [
+            (
+                ComputeBudgetInstruction::request_heap_frame(64 * 1024),
+                ComputeBudgetInstructionSummary::RequestHeapFrame { bytes: 64 * 1024 },
+            ),
+            (
+                ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+                ComputeBudgetInstructionSummary::SetComputeUnitLimit { units: 200_000 },
+            ),
+            (
+                ComputeBudgetInstruction::set_compute_unit_price(1_000),
+                ComputeBudgetInstructionSummary::SetComputeUnitPrice {
+                    micro_lamports: 1_000,
+                },
+            ),
+            (
+                ComputeBudgetInstruction::set_loaded_accounts_data_size_limit(32 * 1024),
+                ComputeBudgetInstructionSummary::SetLoadedAccountsDataSizeLimit {
+                    bytes: 32 * 1024,
+                },
+            ),
+        ];
+        for (instruction, expected) in cases {
+            assert_eq!(instruction.program_id, COMPUTE_BUDGET_PROGRAM_ID);
+            assert_eq!(
+                decode(instruction),
+                DecodedInstruction::ComputeBudget(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn decodes_associated_token_create() {
+        let (funder, wallet, mint) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let program = spl_token::id();
+        let account = associated_token_address(&wallet, &mint, &program);
+        assert_eq!(
+            account,
+            get_associated_token_address_with_program_id(&wallet, &mint, &program)
+        );
+
+        for (instruction, idempotent) in [
+            (
+                associated_token_instruction::create_associated_token_account(
+                    &funder, &wallet, &mint, &program,
+                ),
+                false,
+            ),
+            (
+                associated_token_instruction::create_associated_token_account_idempotent(
+                    &funder, &wallet, &mint, &program,
+                ),
+                true,
+            ),
+        ] {
+            assert_eq!(
+                decode(instruction),
+                DecodedInstruction::AssociatedToken(AssociatedTokenInstructionSummary::Create {
+                    funder: key(&funder),
+                    account: key(&account),
+                    wallet: key(&wallet),
+                    mint: key(&mint),
+                    idempotent,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn decodes_associated_token_recover_nested() {
+        let (wallet, owner_mint, nested_mint) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let program = spl_token::id();
+        let owner_account = associated_token_address(&wallet, &owner_mint, &program);
+
+        assert_eq!(
+            decode(associated_token_instruction::recover_nested(
+                &wallet,
+                &owner_mint,
+                &nested_mint,
+                &program
+            )),
+            DecodedInstruction::AssociatedToken(AssociatedTokenInstructionSummary::RecoverNested {
+                nested_account: key(&associated_token_address(
+                    &owner_account,
+                    &nested_mint,
+                    &program
+                )),
+                destination: key(&associated_token_address(&wallet, &nested_mint, &program)),
+                wallet: key(&wallet),
+            })
+        );
+    }
+
+    #[test]
+    fn truncated_data_is_not_decoded() {
+        let (source, destination, owner) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let program = spl_token::id();
+        let truncate = |mut instruction: Instruction, len: usize| {
+            instruction.data.truncate(len);
+            instruction
+        };
+
+        let transfer =
+            token_instruction::transfer(&program, &source, &destination, &owner, &[], 42).unwrap();
+        let initialize_mint =
+            token_instruction::initialize_mint2(&program, &source, &owner, None, 6).unwrap();
+        let set_authority = token_instruction::set_authority(
+            &program,
+            &source,
+            None,
+            AuthorityType::AccountOwner,
+            &owner,
+            &[],
+        )
+        .unwrap();
+        for instruction in [
+            truncate(transfer.clone(), 0),
+            truncate(transfer, 5),
+            truncate(initialize_mint, 20),
+            truncate(set_authority, 1),
+            truncate(system_instruction::transfer(&source, &destination, 7), 6),
+            truncate(ComputeBudgetInstruction::set_compute_unit_price(1_000), 5),
+            truncate(ComputeBudgetInstruction::set_compute_unit_limit(1_000), 1),
+        ] {
+            let data = instruction.data.clone();
+            assert_eq!(decode(instruction), DecodedInstruction::Unknown { data });
+        }
+    }
+
+    #[test]
+    fn malformed_instructions_are_not_decoded() {
+        let (source, destination, owner) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let program = spl_token::id();
+
+        // COption flag other than 0 or 1
+        let mut set_authority = token_instruction::set_authority(
+            &program,
+            &source,
+            None,
+            AuthorityType::AccountOwner,
+            &owner,
+            &[],
+        )
+        .unwrap();
+        set_authority.data[2] = 2;
+        // Recover nested needs seven accounts
+        let mut recover_nested =
+            associated_token_instruction::recover_nested(&owner, &source, &destination, &program);
+        recover_nested.accounts.truncate(5);
+        // Transfer without its destination account
+        let mut transfer =
+            token_instruction::transfer(&program, &source, &destination, &owner, &[], 42).unwrap();
+        transfer.accounts.truncate(1);
+        let unknown_compute_budget =
+            Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM_ID, &[0, 1, 2, 3, 4], vec![]);
+        let unknown_associated_token =
+            Instruction::new_with_bytes(ASSOCIATED_TOKEN_PROGRAM_ID, &[3], vec![]);
+
+        for instruction in [
+            set_authority,
+            recover_nested,
+            transfer,
+            unknown_compute_budget,
+            unknown_associated_token,
+        ] {
+            let data = instruction.data.clone();
+            assert_eq!(decode(instruction), DecodedInstruction::Unknown { data });
+        }
+    }
+}
@@ -0,0 +1,500 @@
+//! Token launches: pick a mint, build the `tokens/create` transactions,
+//! sign them with the dev wallets and the mint keypair, and submit them as a
+//! Jito bundle.
+//!
+//! ```no_run
+//! # use fury_sdk::{launch::{LaunchBuilder, MintSource}, sdk::{FurySDK, TokenCreationMetadata}, signer::AsyncSigner};
+//! # async fn run(fury: &FurySDK, dev: &solana_sdk::signature::Keypair) -> anyhow::Result<()> {
+//! let launch = LaunchBuilder::new(
+//!     fury,
+//!     TokenCreationMetadata {
+//!         name: "Test Token".to_string(),
+//!         symbol: "TEST".to_string(),
+//!         description: None,
+//!         telegram: None,
+//!         twitter: None,
+//!         website: None,
+//!         file: "https://example.com/logo.png".to_string(),
+//!     },
+//! )
+//! .with_mint(MintSource::Generate)
+//! .with_default_sol_amount("0.5 SOL".parse()?)
+//! .launch(&[dev as &dyn AsyncSigner])
+//! .await?;
+//! println!("Mint {}: {:?}", launch.mint, launch.send.jito_result());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt::{self, Display};
+
+use solana_sdk::{
+    pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::VersionedTransaction,
+};
+
+use crate::{
+    amount::Lamports,
+    api::FuryApi,
+    bundle::{BundleStatusReport, BundleTracker},
+    mint_pool::PooledMint,
+    sdk::{
+        Delivery, FuryError, FurySDK, SendOutcome, TokenCreation, TokenCreationConfig,
+        TokenCreationMetadata, TokensCreateRequest,
+    },
+    signer::AsyncSigner,
+    utils::SignError,
+};
+
+/// Where the mint address of a launch comes from
+#[derive(Debug, Default)]
+pub enum MintSource {
+    /// `utilities/generate-mint`. The API keeps the keypair, so the mint
+    /// signature has to be on the returned transactions already or come
+    /// from the signer set.
+    #[default]
+    Api,
+    /// A fresh keypair generated locally
+    Generate,
+    /// A keypair held by the caller, e.g. a vanity address
+    Keypair(Keypair),
+    /// A mint whose keypair is held elsewhere, e.g. by the API. Its
+    /// signature has to be on the transactions already or come from the
+    /// signer set.
+    Pubkey(Pubkey),
+}
+
+impl From<Keypair> for MintSource {
+    fn from(keypair: Keypair) -> Self {
+        MintSource::Keypair(keypair)
+    }
+}
+
+impl From<PooledMint> for MintSource {
+    fn from(mint: PooledMint) -> Self {
+        match mint {
+            PooledMint::Local(keypair) => MintSource::Keypair(keypair),
+            PooledMint::Remote(pubkey) => MintSource::Pubkey(pubkey),
+        }
+    }
+}
+
+/// Outcome of a launch whose bundle was accepted by the API
+#[derive(Debug)]
+pub struct LaunchResult {
+    pub mint: Pubkey,
+    /// Keypair of the mint, if it was generated or passed in locally
+    pub mint_keypair: Option<Keypair>,
+    /// Signed transactions, in bundle order
+    pub transactions: Vec<VersionedTransaction>,
+    pub send: SendOutcome,
+    /// Final status of the bundle, if a `BundleTracker` was configured
+    pub bundle: Option<BundleStatusReport>,
+}
+
+/// Errors after the mint was picked hand back its keypair, if it was held
+/// locally, so it can be reused or returned to a `MintPool`
+#[derive(Debug)]
+pub enum LaunchError {
+    /// The mint address could not be generated
+    Mint(FuryError),
+    /// The `tokens/create` endpoint failed, or the request was invalid
+    /// and not sent
+    Request {
+        mint_keypair: Option<Keypair>,
+        source: FuryError,
+    },
+    /// A required signature is not covered by the signer set, or signing
+    /// failed. Nothing was sent.
+    Sign {
+        mint_keypair: Option<Keypair>,
+        source: SignError,
+    },
+    /// The send failed or the API did not accept the bundle
+    Send {
+        mint_keypair: Option<Keypair>,
+        source: FuryError,
+    },
+    /// The bundle was sent, but its status could not be queried
+    Bundle {
+        mint: Pubkey,
+        mint_keypair: Option<Keypair>,
+        send: SendOutcome,
+        source: FuryError,
+    },
+}
+
+impl LaunchError {
+    /// Keypair of the mint, if it was generated or passed in locally
+    pub fn into_mint_keypair(self) -> Option<Keypair> {
+        match self {
+            LaunchError::Mint(_) => None,
+            LaunchError::Request { mint_keypair, .. }
+            | LaunchError::Sign { mint_keypair, .. }
+            | LaunchError::Send { mint_keypair, .. }
+            | LaunchError::Bundle { mint_keypair, .. } => mint_keypair,
+        }
+    }
+}
+
+impl Display for LaunchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LaunchError::Mint(e) => write!(f, "Mint generation failed: {}", e),
+            LaunchError::Request { source, .. } => write!(f, "Token creation failed: {}", source),
+            LaunchError::Sign { source, .. } => write!(f, "Signing failed: {}", source),
+            LaunchError::Send { source, .. } => write!(f, "Bundle send failed: {}", source),
+            LaunchError::Bundle { mint, source, .. } => {
+                write!(
+                    f,
+                    "Bundle for mint {} sent, status failed: {}",
+                    mint, source
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for LaunchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LaunchError::Mint(e) => Some(e),
+            LaunchError::Request { source, .. }
+            | LaunchError::Send { source, .. }
+            | LaunchError::Bundle { source, .. } => Some(source),
+            LaunchError::Sign { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Runs a token launch end to end: mint, `tokens/create`, signer coverage
+/// check, signing and bundle submission. Any `FuryApi` can serve the
+/// requests.
+pub struct LaunchBuilder<'a, A: FuryApi + ?Sized = FurySDK> {
+    sdk: &'a A,
+    pub metadata: TokenCreationMetadata,
+    pub mint: MintSource,
+    /// Dev wallets buying at launch. Defaults to the signer set.
+    pub wallet_addresses: Option<Vec<Pubkey>>,
+    pub default_sol_amount: Lamports,
+    /// Buy amount per wallet. Defaults to `default_sol_amount` for each.
+    pub amounts: Option<Vec<Lamports>>,
+    pub delivery: Delivery,
+    pub bundle_tracker: Option<BundleTracker>,
+}
+
+impl<'a, A: FuryApi + ?Sized> LaunchBuilder<'a, A> {
+    pub fn new(sdk: &'a A, metadata: TokenCreationMetadata) -> Self {
+        Self {
+            sdk,
+            metadata,
+            mint: MintSource::default(),
+            wallet_addresses: None,
+            default_sol_amount: Lamports::default(),
+            amounts: None,
+            delivery: Delivery::Jito,
+            bundle_tracker: None,
+        }
+    }
+
+    pub fn with_mint(mut self, mint: impl Into<MintSource>) -> Self {
+        self.mint = mint.into();
+        self
+    }
+
+    pub fn with_wallet_addresses(mut self, wallet_addresses: Vec<Pubkey>) -> Self {
+        self.wallet_addresses = Some(wallet_addresses);
+        self
+    }
+
+    pub fn with_default_sol_amount(mut self, default_sol_amount: Lamports) -> Self {
+        self.default_sol_amount = default_sol_amount;
+        self
+    }
+
+    pub fn with_amounts(mut self, amounts: Vec<Lamports>) -> Self {
+        self.amounts = Some(amounts);
+        self
+    }
+
+    pub fn with_delivery(mut self, delivery: Delivery) -> Self {
+        self.delivery = delivery;
+        self
+    }
+
+    /// Waits for the bundle to land, fail or be dropped before returning.
+    /// Only used with `Delivery::Jito`.
+    pub fn with_bundle_tracker(mut self, bundle_tracker: BundleTracker) -> Self {
+        self.bundle_tracker = Some(bundle_tracker);
+        self
+    }
+
+    /// Runs the launch. `signers` must cover every dev wallet and any other
+    /// account the transactions need; the mint keypair is added when it is
+    /// held locally.
+    pub async fn launch(self, signers: &[&dyn AsyncSigner]) -> Result<LaunchResult, LaunchError> {
+        let (mint, mint_keypair) = match self.mint {
+            MintSource::Api => {
+                let generated = self.sdk.generate_mint().await.map_err(LaunchError::Mint)?;
+                (generated.pubkey, None)
+            }
+            MintSource::Generate => {
+                let keypair = Keypair::new();
+                (keypair.pubkey(), Some(keypair))
+            }
+            MintSource::Keypair(keypair) => (keypair.pubkey(), Some(keypair)),
+            MintSource::Pubkey(pubkey) => (pubkey, None),
+        };
+
+        let wallet_addresses = self.wallet_addresses.unwrap_or_else(|| {
+            signers
+                .iter()
+                .map(|signer| signer.signer_pubkey())
+                .collect()
+        });
+        let amounts = self
+            .amounts
+            .unwrap_or_else(|| vec![self.default_sol_amount; wallet_addresses.len()]);
+        if amounts.len() != wallet_addresses.len() {
+            return Err(LaunchError::Request {
+                mint_keypair,
+                source: FuryError::ConfigError(format!(
+                    "{} amounts for {} wallets",
+                    amounts.len(),
+                    wallet_addresses.len()
+                )),
+            });
+        }
+        let request = TokensCreateRequest {
+            wallet_addresses,
+            mint_pubkey: mint,
+            config: TokenCreationConfig {
+                token_creation: TokenCreation {
+                    metadata: self.metadata,
+                    default_sol_amount: self.default_sol_amount,
+                },
+            },
+            amounts,
+        };
+        let unsigned = match self.sdk.tokens_create(&request).await {
+            Ok(response) => response.transactions,
+            Err(source) => {
+                return Err(LaunchError::Request {
+                    mint_keypair,
+                    source,
+                })
+            }
+        };
+
+        let signed = {
+            let mut signers = signers.to_vec();
+            if let Some(keypair) = &mint_keypair {
+                signers.push(keypair);
+            }
+            // Checked up front so remote signers are not asked to sign a
+            // bundle that can never be sent
+            let signer_pubkeys: Vec<Pubkey> = signers
+                .iter()
+                .map(|signer| signer.signer_pubkey())
+                .collect();
+            match unsigned.check_signers(&signer_pubkeys) {
+                Ok(()) => unsigned.sign_async(&signers).await,
+                Err(e) => Err(e),
+            }
+        };
+        let transactions = match signed {
+            Ok(transactions) => transactions,
+            Err(source) => {
+                return Err(LaunchError::Sign {
+                    mint_keypair,
+                    source,
+                })
+            }
+        };
+
+        let send = match self
+            .sdk
+            .send_transactions(&transactions, self.delivery)
+            .await
+            .and_then(SendOutcome::into_result)
+        {
+            Ok(send) => send,
+            Err(source) => {
+                return Err(LaunchError::Send {
+                    mint_keypair,
+                    source,
+                })
+            }
+        };
+
+        let mut bundle = None;
+        if let (Some(tracker), Some(bundle_id)) = (&self.bundle_tracker, send.jito_result()) {
+            match tracker.wait_for_bundle(bundle_id).await {
+                Ok(report) => bundle = Some(report),
+                Err(source) => {
+                    return Err(LaunchError::Bundle {
+                        mint,
+                        mint_keypair,
+                        send,
+                        source,
+                    })
+                }
+            }
+        }
+
+        Ok(LaunchResult {
+            mint,
+            mint_keypair,
+            transactions,
+            send,
+            bundle,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_system_interface::{instruction as system_instruction, program as system_program};
+
+    use super::*;
+    use crate::{
+        api::Endpoint,
+        fake::{FakeCall, FakeFuryApi},
+        sdk::TokensCreateResponse,
+        test_support::{jito_sent, unsigned_transaction},
+        transactions::UnsignedTransactions,
+    };
+
+    fn metadata() -> TokenCreationMetadata {
+        TokenCreationMetadata {
+            name: "Test Token".to_string(),
+            symbol: "TEST".to_string(),
+            description: None,
+            telegram: None,
+            twitter: None,
+            website: None,
+            file: "https://example.com/logo.png".to_string(),
+        }
+    }
+
+    /// Creation transaction signed by the dev wallet and the mint
+    fn create(dev: &Pubkey, mint: &Pubkey) -> TokensCreateResponse {
+        let instruction = system_instruction::create_account(dev, mint, 1, 82, &system_program::ID);
+        TokensCreateResponse {
+            success: true,
+            transactions: UnsignedTransactions::new(vec![unsigned_transaction(
+                dev,
+                &[instruction],
+            )]),
+        }
+    }
+
+    #[tokio::test]
+    async fn returns_generated_mint_keypair_when_request_fails() {
+        let dev = Keypair::new();
+        let fake = FakeFuryApi::new();
+        fake.push_error(
+            Endpoint::TokensCreate,
+            FuryError::ConfigError("down".to_string()),
+        );
+
+        let error = LaunchBuilder::new(&fake, metadata())
+            .with_mint(MintSource::Generate)
+            .launch(&[&dev as &dyn AsyncSigner])
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, LaunchError::Request { .. }));
+        let FakeCall::TokensCreate(request) = &fake.calls()[0] else {
+            panic!("expected a tokens/create call");
+        };
+        assert_eq!(
+            error.into_mint_keypair().unwrap().pubkey(),
+            request.mint_pubkey
+        );
+    }
+
+    #[tokio::test]
+    async fn returns_mint_keypair_when_send_fails() {
+        let (dev, mint) = (Keypair::new(), Keypair::new());
+        let mint_pubkey = mint.pubkey();
+        let fake = FakeFuryApi::new();
+        fake.push_response(create(&dev.pubkey(), &mint_pubkey));
+        fake.push_error(
+            Endpoint::SendTransactions,
+            FuryError::ConfigError("down".to_string()),
+        );
+
+        let error = LaunchBuilder::new(&fake, metadata())
+            .with_mint(mint)
+            .launch(&[&dev as &dyn AsyncSigner])
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, LaunchError::Send { .. }));
+        let FakeCall::SendTransactions { transactions, .. } = &fake.calls()[1] else {
+            panic!("expected a transactions/send call");
+        };
+        assert!(transactions[0].verify_with_results().iter().all(|ok| *ok));
+        assert_eq!(error.into_mint_keypair().unwrap().pubkey(), mint_pubkey);
+    }
+
+    #[tokio::test]
+    async fn returns_mint_keypair_when_send_is_not_accepted() {
+        let (dev, mint) = (Keypair::new(), Keypair::new());
+        let mint_pubkey = mint.pubkey();
+        let fake = FakeFuryApi::new();
+        fake.push_response(create(&dev.pubkey(), &mint_pubkey));
+        fake.push_response(jito_sent(false));
+
+        let error = LaunchBuilder::new(&fake, metadata())
+            .with_mint(mint)
+            .launch(&[&dev as &dyn AsyncSigner])
+            .await
+            .unwrap_err();
+
+        let LaunchError::Send { source, .. } = &error else {
+            panic!("expected a send error, got {:?}", error);
+        };
+        assert!(matches!(source, FuryError::ApiError(_)));
+        assert_eq!(error.into_mint_keypair().unwrap().pubkey(), mint_pubkey);
+    }
+
+    #[tokio::test]
+    async fn rejects_amounts_not_matching_wallets() {
+        let dev = Keypair::new();
+        let fake = FakeFuryApi::new();
+
+        let error = LaunchBuilder::new(&fake, metadata())
+            .with_mint(MintSource::Generate)
+            .with_wallet_addresses(vec![dev.pubkey(), Pubkey::new_unique()])
+            .with_amounts(vec![Lamports::new(1_000)])
+            .launch(&[&dev as &dyn AsyncSigner])
+            .await
+            .unwrap_err();
+
+        let LaunchError::Request { source, .. } = &error else {
+            panic!("expected a request error, got {:?}", error);
+        };
+        assert!(matches!(source, FuryError::ConfigError(_)));
+        assert!(fake.calls().is_empty());
+        assert!(error.into_mint_keypair().is_some());
+    }
+
+    #[tokio::test]
+    async fn returns_mint_keypair_when_signer_is_missing() {
+        let (dev, mint) = (Keypair::new(), Keypair::new());
+        let mint_pubkey = mint.pubkey();
+        let fake = FakeFuryApi::new();
+        fake.push_response(create(&Pubkey::new_unique(), &mint_pubkey));
+
+        let error = LaunchBuilder::new(&fake, metadata())
+            .with_mint(mint)
+            .launch(&[&dev as &dyn AsyncSigner])
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, LaunchError::Sign { .. }));
+        assert!(fake.calls_to(Endpoint::SendTransactions).is_empty());
+        assert_eq!(error.into_mint_keypair().unwrap().pubkey(), mint_pubkey);
+    }
+}
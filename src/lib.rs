@@ -1,2 +1,30 @@
+pub mod amount;
+pub mod api;
+pub mod builder;
+pub mod bundle;
+pub mod cassette;
+pub mod confirmation;
+pub mod executor;
+pub mod fake;
+pub mod inspect;
+pub mod launch;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod mint_pool;
+#[cfg(feature = "mock-server")]
+pub mod mock_server;
+pub mod policy;
+pub mod retry;
+pub mod rpc;
 pub mod sdk;
-pub mod utils;
\ No newline at end of file
+mod serde_utils;
+#[cfg(feature = "simulation")]
+pub mod simulation;
+pub mod signer;
+#[cfg(any(test, feature = "mock-server"))]
+pub(crate) mod test_support;
+#[cfg(feature = "tracing")]
+pub mod trace;
+pub mod transactions;
+pub mod utils;
+pub mod vanity;
@@ -1,13 +1,8 @@
 use std::sync::Arc;
 
 use anyhow::Result;
-use fury_sdk::{
-    sdk::{
-        AnalyticsPnlOptions, BuyTokenRequest, FuryError, FurySDK, Protocol, SellRequest, TokenCreation, TokenCreationConfig, TokenCreationMetadata, TokensCreateRequest, TransactionSendRequest, WalletsDistributeRecipient, WalletsDistributeRequest
-    },
-    utils::sign_transactions,
-};
-use solana_sdk::{native_token::sol_to_lamports, signature::Keypair, signer::Signer};
+use fury_sdk::sdk::FurySDK;
+use solana_sdk::{signature::Keypair, signer::Signer};
 
 /**
  * Please note that this file is only intended to be used for testing purposes.
@@ -22,19 +17,23 @@ async fn main() -> Result<()> {
     println!("Enter wallet private key: ");
     std::io::stdin().read_line(&mut input).unwrap();
     let wallet = Arc::new(Keypair::from_base58_string(input.trim()));
+    println!("Wallet: {}", wallet.pubkey());
 
-    let wallets = vec![wallet.clone()];
+    let health = client.health_check().await?;
+    println!("{:#?}", health);
+
+    // let wallets = [wallet.clone()];
 
     // --------------------------------------------
     // Buy token
     // --------------------------------------------
     // let buy_response = match client
     //     .buy_token(&BuyTokenRequest {
-    //         wallet_addresses: wallets.iter().map(|w| w.pubkey().to_string()).collect(),
-    //         token_address: "Bq5nFQ82jBYcFKRzUSximpCmCg5t8L8tVMqsn612pump".to_string(),
-    //         sol_amount: 0.001,
+    //         wallet_addresses: wallets.iter().map(|w| w.pubkey()).collect(),
+    //         token_address: pubkey!("Bq5nFQ82jBYcFKRzUSximpCmCg5t8L8tVMqsn612pump"),
+    //         sol_amount: "0.001 SOL".parse()?,
     //         protocol: Protocol::Pumpfun,
-    //         jito_tip_lamports: Some(sol_to_lamports(0.001)),
+    //         jito_tip_lamports: Some(Lamports::from_sol_str("0.001")?.lamports()),
     //         amounts: None,
     //         use_rpc: false,
     //         affiliate_address: None,
@@ -53,7 +52,7 @@ async fn main() -> Result<()> {
     // println!("Buy response:");
     // println!("{:#?}", buy_response);
 
-    // let signed_txs = match sign_transactions(&buy_response.transactions, &wallets) {
+    // let signed_txs = match buy_response.transactions.sign_to_base58(&wallets) {
     //     Ok(signed_txs) => signed_txs,
     //     Err(err) => {
     //         println!("{:#?}", err);
@@ -68,8 +67,8 @@ async fn main() -> Result<()> {
     // Sell token
     // --------------------------------------------
     // let sell_response = match client.sell_token(&SellRequest {
-    //     wallet_addresses: vec![wallet.pubkey().to_string()],
-    //     token_address: "Bq5nFQ82jBYcFKRzUSximpCmCg5t8L8tVMqsn612pump".to_string(),
+    //     wallet_addresses: vec![wallet.pubkey()],
+    //     token_address: pubkey!("Bq5nFQ82jBYcFKRzUSximpCmCg5t8L8tVMqsn612pump"),
     //     percentage: 100,
     //     protocol: Protocol::Pumpfun,
     //     affiliate_address: None,
@@ -87,7 +86,7 @@ async fn main() -> Result<()> {
     // println!("Sell response:");
     // println!("{:#?}", sell_response);
 
-    // let signed_txs = match sign_transactions(&sell_response.transactions, &wallets) {
+    // let signed_txs = match sell_response.transactions.sign_to_base58(&wallets) {
     //     Ok(signed_txs) => signed_txs,
     //     Err(err) => {
     //         println!("{:#?}", err);
@@ -102,13 +101,13 @@ async fn main() -> Result<()> {
     // println!("{:#?}", mint);
 
     // let pnl_wallets = vec![
-    //     "Bp7RdpR7HHJwWdQ8TXmGpFEkP1pBFHdviNQn45mrFSCo".to_string(),
-    //     "Bp7RdpR7HHJwWdQ8TXmGpFEkP1pBFHdviNQn45mrFSCo".to_string(),
+    //     pubkey!("Bp7RdpR7HHJwWdQ8TXmGpFEkP1pBFHdviNQn45mrFSCo"),
+    //     pubkey!("Bp7RdpR7HHJwWdQ8TXmGpFEkP1pBFHdviNQn45mrFSCo"),
     // ];
     // let pnl = client
     //     .analytics_pnl(
-    //         pnl_wallets,
-    //         "Bq5nFQ82jBYcFKRzUSximpCmCg5t8L8tVMqsn612pump".to_string(),
+    //         &pnl_wallets,
+    //         pubkey!("Bq5nFQ82jBYcFKRzUSximpCmCg5t8L8tVMqsn612pump"),
     //         AnalyticsPnlOptions {
     //             include_timestamp: true,
     //         },
@@ -120,8 +119,8 @@ async fn main() -> Result<()> {
     // Create token
     // --------------------------------------------
     // let create_token_response = client.tokens_create(&TokensCreateRequest {
-    //     wallet_addresses: vec![wallet.pubkey().to_string()],
-    //     mint_pubkey: "Bq5nFQ82jBYcFKRzUSximpCmCg5t8L8tVMqsn612pump".to_string(),
+    //     wallet_addresses: vec![wallet.pubkey()],
+    //     mint_pubkey: pubkey!("Bq5nFQ82jBYcFKRzUSximpCmCg5t8L8tVMqsn612pump"),
     //     config: TokenCreationConfig {
     //         token_creation: TokenCreation {
     //             metadata: TokenCreationMetadata {
@@ -133,10 +132,10 @@ async fn main() -> Result<()> {
     //                 website: None,
     //                 file: "https://example.com/logo.png".to_string(),
     //             },
-    //             default_sol_amount: 0.001,
+    //             default_sol_amount: "0.001 SOL".parse()?,
     //         },
     //     },
-    //     amounts: vec![0.001],
+    //     amounts: vec!["0.001 SOL".parse()?],
     // }).await;
 
     // println!("{:#?}", create_token_response);
@@ -145,10 +144,10 @@ async fn main() -> Result<()> {
     // Wallets distribute
     // --------------------------------------------
     // let wallets_distribute_response = client.wallets_distribute(&WalletsDistributeRequest {
-    //     sender: wallet.pubkey().to_string(),
+    //     sender: wallet.pubkey(),
     //     recipients: vec![WalletsDistributeRecipient {
-    //         address: "5tqe3S1zsfAmT7L2Ru5gVJDaq4wUB7AbCpTLPaxaM6eG".to_string(),
-    //         amount: 0.001,
+    //         address: pubkey!("5tqe3S1zsfAmT7L2Ru5gVJDaq4wUB7AbCpTLPaxaM6eG"),
+    //         amount: "0.001 SOL".parse()?,
     //     }],
     // }).await;
 
@@ -175,10 +174,7 @@ async fn main() -> Result<()> {
     
 
     // match client
-    //     .jito_transaction_send(&TransactionSendRequest {
-    //         transactions: signed_txs,
-    //         use_rpc: false,
-    //     })
+    //     .send_transactions(&signed_txs, Delivery::Jito)
     //     .await
     // {
     //     Ok(response) => println!("{:#?}", response),
@@ -0,0 +1,103 @@
+//! Usage metrics, enabled by the `metrics` feature. They are emitted
+//! through the [`metrics`](https://docs.rs/metrics) facade, so any recorder
+//! installed by the application collects them, e.g.
+//! `metrics_exporter_prometheus::PrometheusBuilder` for a scrape endpoint.
+//!
+//! | Name | Type | Labels |
+//! | --- | --- | --- |
+//! | `fury_requests_total` | counter | `endpoint`, `status_class` |
+//! | `fury_request_duration_seconds` | histogram | `endpoint` |
+//! | `fury_request_retries_total` | counter | `endpoint` |
+//! | `fury_signed_transactions_total` | counter | |
+//! | `fury_sends_total` | counter | `delivery`, `result` |
+//!
+//! Requests are counted per HTTP attempt, so a retried call counts once per
+//! attempt. `status_class` is `2xx` to `5xx`, or `error` when no response
+//! was received. Sends are counted per `send_transactions` call and fail
+//! when the request fails or the API reports failure.
+//!
+//! ```no_run
+//! // After installing the recorder, so the descriptions reach it
+//! fury_sdk::metrics::describe();
+//! ```
+
+use std::time::Duration;
+
+use reqwest::StatusCode;
+
+use crate::sdk::Delivery;
+
+pub const REQUESTS: &str = "fury_requests_total";
+pub const REQUEST_DURATION: &str = "fury_request_duration_seconds";
+pub const RETRIES: &str = "fury_request_retries_total";
+pub const SIGNED_TRANSACTIONS: &str = "fury_signed_transactions_total";
+pub const SENDS: &str = "fury_sends_total";
+
+/// Registers the unit and description of every metric with the installed
+/// recorder
+pub fn describe() {
+    use ::metrics::{describe_counter, describe_histogram, Unit};
+
+    describe_counter!(
+        REQUESTS,
+        Unit::Count,
+        "FURY API requests by endpoint and status class"
+    );
+    describe_histogram!(
+        REQUEST_DURATION,
+        Unit::Seconds,
+        "Latency of FURY API requests by endpoint"
+    );
+    describe_counter!(
+        RETRIES,
+        Unit::Count,
+        "FURY API requests retried by endpoint"
+    );
+    describe_counter!(
+        SIGNED_TRANSACTIONS,
+        Unit::Count,
+        "Transactions fully signed"
+    );
+    describe_counter!(
+        SENDS,
+        Unit::Count,
+        "Transaction submissions by delivery method and result"
+    );
+}
+
+pub(crate) fn record_request(endpoint: &str, status: Option<StatusCode>, latency: Duration) {
+    let status_class = match status.map(|status| status.as_u16()) {
+        Some(100..=199) => "1xx",
+        Some(200..=299) => "2xx",
+        Some(300..=399) => "3xx",
+        Some(400..=499) => "4xx",
+        Some(_) => "5xx",
+        None => "error",
+    };
+    ::metrics::counter!(
+        REQUESTS,
+        "endpoint" => endpoint.to_string(),
+        "status_class" => status_class,
+    )
+    .increment(1);
+    ::metrics::histogram!(REQUEST_DURATION, "endpoint" => endpoint.to_string())
+        .record(latency.as_secs_f64());
+}
+
+pub(crate) fn record_retry(endpoint: &str) {
+    ::metrics::counter!(RETRIES, "endpoint" => endpoint.to_string()).increment(1);
+}
+
+pub(crate) fn record_signed_transaction() {
+    ::metrics::counter!(SIGNED_TRANSACTIONS).increment(1);
+}
+
+/// `success` is false when the request failed or the API reported failure
+pub(crate) fn record_send(delivery: Delivery, success: bool) {
+    let delivery = match delivery {
+        Delivery::Jito => "jito",
+        Delivery::Rpc => "rpc",
+    };
+    let result = if success { "success" } else { "failure" };
+    ::metrics::counter!(SENDS, "delivery" => delivery, "result" => result).increment(1);
+}
@@ -0,0 +1,623 @@
+//! Pool of mint addresses prepared ahead of launches, refilled on a
+//! background tokio task.
+//!
+//! ```no_run
+//! # use fury_sdk::{mint_pool::MintPool, vanity::VanityGrinder};
+//! # async fn run() -> Result<(), fury_sdk::mint_pool::MintPoolError> {
+//! let pool = MintPool::spawn(VanityGrinder::new().with_suffix("pump"), 4);
+//! let mint = pool.take().await?;
+//! println!("Launching {}", mint.pubkey());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::{self, Display},
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use tokio::{sync::Notify, task::JoinHandle, time::sleep};
+
+use crate::{
+    sdk::FurySDK,
+    vanity::{CancelToken, VanityGrinder},
+};
+
+/// A mint address ready for a launch
+#[derive(Debug)]
+pub enum PooledMint {
+    /// Generated or ground locally, so the pool holds the secret key
+    Local(Keypair),
+    /// Returned by `generate_mint`. The API keeps the keypair.
+    Remote(Pubkey),
+}
+
+impl PooledMint {
+    pub fn pubkey(&self) -> Pubkey {
+        match self {
+            PooledMint::Local(keypair) => keypair.pubkey(),
+            PooledMint::Remote(pubkey) => *pubkey,
+        }
+    }
+}
+
+/// Produces the mints that refill a `MintPool`
+#[async_trait]
+pub trait MintGenerator: Send + Sync {
+    /// Generates one mint. Long running generators should stop once
+    /// `cancel` is triggered, which happens when the pool is dropped.
+    async fn generate(&self, cancel: &CancelToken) -> anyhow::Result<PooledMint>;
+}
+
+#[async_trait]
+impl<T: MintGenerator + ?Sized> MintGenerator for Arc<T> {
+    async fn generate(&self, cancel: &CancelToken) -> anyhow::Result<PooledMint> {
+        self.as_ref().generate(cancel).await
+    }
+}
+
+/// Random local keypairs, without a vanity pattern
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomMint;
+
+#[async_trait]
+impl MintGenerator for RandomMint {
+    async fn generate(&self, _cancel: &CancelToken) -> anyhow::Result<PooledMint> {
+        Ok(PooledMint::Local(Keypair::new()))
+    }
+}
+
+/// Grinds on blocking threads. Progress is not reported.
+#[async_trait]
+impl MintGenerator for VanityGrinder {
+    async fn generate(&self, cancel: &CancelToken) -> anyhow::Result<PooledMint> {
+        let grinder = self.clone();
+        let cancel = cancel.clone();
+        let keypair = tokio::task::spawn_blocking(move || grinder.grind(&cancel, |_| {})).await??;
+        Ok(PooledMint::Local(keypair))
+    }
+}
+
+/// Mints from `utilities/generate-mint`
+#[async_trait]
+impl MintGenerator for FurySDK {
+    async fn generate(&self, _cancel: &CancelToken) -> anyhow::Result<PooledMint> {
+        Ok(PooledMint::Remote(self.generate_mint().await?.pubkey))
+    }
+}
+
+/// Persistence hooks that let a pool survive restarts.
+///
+/// Stores hold secret keys of `Local` mints, so keep them somewhere as safe
+/// as the dev wallets.
+#[async_trait]
+pub trait MintStore: Send + Sync {
+    /// Mints to put back in the pool on startup. Must not include mints
+    /// passed to `mark_taken`.
+    async fn load(&self) -> anyhow::Result<Vec<PooledMint>>;
+
+    /// Called before a new mint enters the pool
+    async fn save(&self, mint: &PooledMint) -> anyhow::Result<()>;
+
+    /// Called before a mint is handed out. If this fails, or the take is
+    /// cancelled first, the mint stays in the pool, so it is never handed
+    /// out twice. It may then be marked again on a later take.
+    async fn mark_taken(&self, mint: &Pubkey) -> anyhow::Result<()>;
+}
+
+#[async_trait]
+impl<T: MintStore + ?Sized> MintStore for Arc<T> {
+    async fn load(&self) -> anyhow::Result<Vec<PooledMint>> {
+        self.as_ref().load().await
+    }
+
+    async fn save(&self, mint: &PooledMint) -> anyhow::Result<()> {
+        self.as_ref().save(mint).await
+    }
+
+    async fn mark_taken(&self, mint: &Pubkey) -> anyhow::Result<()> {
+        self.as_ref().mark_taken(mint).await
+    }
+}
+
+#[derive(Debug)]
+pub enum MintPoolError {
+    /// The store failed to load, save or mark a mint
+    Store(anyhow::Error),
+    /// The pool is empty and the refill task has stopped, e.g. after the
+    /// generator panicked
+    Closed,
+}
+
+impl Display for MintPoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MintPoolError::Store(e) => write!(f, "Mint store error: {}", e),
+            MintPoolError::Closed => write!(f, "Mint pool refill task stopped"),
+        }
+    }
+}
+
+impl std::error::Error for MintPoolError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MintPoolError::Store(e) => Some(e.as_ref()),
+            MintPoolError::Closed => None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    ready: VecDeque<PooledMint>,
+    /// Every mint that entered the pool, so a generator or store returning
+    /// the same address twice cannot cause a double handout
+    seen: HashSet<Pubkey>,
+    /// Mints popped by a `try_take` still waiting on the store. They count
+    /// toward the capacity, as they return to the pool if the take fails.
+    reserved: usize,
+    /// Set once the refill task has stopped
+    closed: bool,
+    last_error: Option<String>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    capacity: usize,
+    generator: Box<dyn MintGenerator>,
+    store: Option<Box<dyn MintStore>>,
+    retry_delay: Duration,
+    /// Wakes the refill task after a mint is taken
+    refill: Notify,
+    /// Wakes a waiting `take` after a mint is added
+    ready: Notify,
+    cancel: CancelToken,
+}
+
+impl Shared {
+    /// Adds `mint` unless its address was seen before. Returns whether it
+    /// was added.
+    fn push(&self, mint: PooledMint) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !state.seen.insert(mint.pubkey()) {
+            return false;
+        }
+        state.ready.push_back(mint);
+        drop(state);
+        self.ready.notify_one();
+        true
+    }
+
+    fn set_error(&self, error: impl Display) {
+        self.state.lock().unwrap().last_error = Some(error.to_string());
+    }
+
+    async fn run(self: Arc<Self>) {
+        let _closed = CloseOnExit(&self);
+        while !self.cancel.is_cancelled() {
+            let len = {
+                let state = self.state.lock().unwrap();
+                state.ready.len() + state.reserved
+            };
+            if len >= self.capacity {
+                self.refill.notified().await;
+                continue;
+            }
+
+            let mint = match self.generator.generate(&self.cancel).await {
+                Ok(mint) => mint,
+                Err(_) if self.cancel.is_cancelled() => break,
+                Err(e) => {
+                    self.set_error(e);
+                    sleep(self.retry_delay).await;
+                    continue;
+                }
+            };
+            if self.state.lock().unwrap().seen.contains(&mint.pubkey()) {
+                self.set_error(format!("Duplicate mint {}", mint.pubkey()));
+                sleep(self.retry_delay).await;
+                continue;
+            }
+            // Only persisted mints enter the pool, so nothing is lost on a
+            // restart
+            if let Some(store) = &self.store {
+                if let Err(e) = store.save(&mint).await {
+                    self.set_error(MintPoolError::Store(e));
+                    sleep(self.retry_delay).await;
+                    continue;
+                }
+            }
+            self.push(mint);
+        }
+    }
+}
+
+/// Marks the pool closed when the refill task ends, panics included, and
+/// wakes every waiting `take`
+struct CloseOnExit<'a>(&'a Shared);
+
+impl Drop for CloseOnExit<'_> {
+    fn drop(&mut self) {
+        let shared = self.0;
+        // Runs while unwinding, so a poisoned lock must not panic again
+        shared
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .closed = true;
+        shared.ready.notify_waiters();
+    }
+}
+
+/// Keeps up to `capacity` mints ready and refills the pool in the background
+/// as they are taken. Each mint is handed out at most once.
+///
+/// Dropping the pool stops the refill task.
+pub struct MintPool {
+    shared: Arc<Shared>,
+    task: JoinHandle<()>,
+}
+
+impl MintPool {
+    /// Starts filling a pool of `capacity` mints. Must be called within a
+    /// tokio runtime.
+    pub fn spawn(generator: impl MintGenerator + 'static, capacity: usize) -> Self {
+        Self::start(Box::new(generator), None, capacity, Vec::new())
+    }
+
+    /// Loads the mints saved in `store`, then starts filling the pool. New
+    /// mints are saved before they enter the pool.
+    pub async fn spawn_with_store(
+        generator: impl MintGenerator + 'static,
+        capacity: usize,
+        store: impl MintStore + 'static,
+    ) -> Result<Self, MintPoolError> {
+        let saved = store.load().await.map_err(MintPoolError::Store)?;
+        Ok(Self::start(
+            Box::new(generator),
+            Some(Box::new(store)),
+            capacity,
+            saved,
+        ))
+    }
+
+    fn start(
+        generator: Box<dyn MintGenerator>,
+        store: Option<Box<dyn MintStore>>,
+        capacity: usize,
+        saved: Vec<PooledMint>,
+    ) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State::default()),
+            capacity,
+            generator,
+            store,
+            retry_delay: Duration::from_secs(1),
+            refill: Notify::new(),
+            ready: Notify::new(),
+            cancel: CancelToken::new(),
+        });
+        for mint in saved {
+            shared.push(mint);
+        }
+        let task = tokio::spawn(shared.clone().run());
+        Self { shared, task }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+
+    /// Mints ready to be taken
+    pub fn len(&self) -> usize {
+        self.shared.state.lock().unwrap().ready.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Last error of the generator or store during a refill. Refills are
+    /// retried, so this is informational.
+    pub fn last_error(&self) -> Option<String> {
+        self.shared.state.lock().unwrap().last_error.clone()
+    }
+
+    /// Takes a mint, waiting for the refill task if the pool is empty.
+    /// Fails with `Closed` if the pool is empty and the task has stopped.
+    pub async fn take(&self) -> Result<PooledMint, MintPoolError> {
+        loop {
+            // Registered before checking, so the wakeup of a closing task
+            // cannot be missed
+            let notified = self.shared.ready.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(mint) = self.try_take().await? {
+                return Ok(mint);
+            }
+            if self.shared.state.lock().unwrap().closed {
+                return Err(MintPoolError::Closed);
+            }
+            notified.await;
+        }
+    }
+
+    /// Takes a mint if one is ready. Cancel safe: a mint is only removed
+    /// once the store has marked it taken.
+    pub async fn try_take(&self) -> Result<Option<PooledMint>, MintPoolError> {
+        let mint = {
+            let mut state = self.shared.state.lock().unwrap();
+            let Some(mint) = state.ready.pop_front() else {
+                return Ok(None);
+            };
+            state.reserved += 1;
+            mint
+        };
+        let pubkey = mint.pubkey();
+        let reserved = Reserved {
+            shared: &self.shared,
+            mint: Some(mint),
+        };
+
+        if let Some(store) = &self.shared.store {
+            store
+                .mark_taken(&pubkey)
+                .await
+                .map_err(MintPoolError::Store)?;
+        }
+        Ok(Some(reserved.into_inner()))
+    }
+}
+
+/// A mint popped by `try_take`, put back at the front of the pool when
+/// dropped before the handout
+struct Reserved<'a> {
+    shared: &'a Shared,
+    mint: Option<PooledMint>,
+}
+
+impl Reserved<'_> {
+    /// Hands the mint out and lets the refill task replace it
+    fn into_inner(mut self) -> PooledMint {
+        let mint = self.mint.take().unwrap();
+        self.shared.state.lock().unwrap().reserved -= 1;
+        self.shared.refill.notify_one();
+        mint
+    }
+}
+
+impl Drop for Reserved<'_> {
+    fn drop(&mut self) {
+        if let Some(mint) = self.mint.take() {
+            let mut state = self.shared.state.lock().unwrap();
+            state.ready.push_front(mint);
+            state.reserved -= 1;
+            drop(state);
+            self.shared.ready.notify_one();
+        }
+    }
+}
+
+impl Drop for MintPool {
+    fn drop(&mut self) {
+        self.shared.cancel.cancel();
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Never produces a mint, so only saved mints are in the pool
+    struct Idle;
+
+    #[async_trait]
+    impl MintGenerator for Idle {
+        async fn generate(&self, _cancel: &CancelToken) -> anyhow::Result<PooledMint> {
+            std::future::pending().await
+        }
+    }
+
+    /// Fresh keypairs, counted
+    #[derive(Default)]
+    struct Counting(AtomicUsize);
+
+    #[async_trait]
+    impl MintGenerator for Counting {
+        async fn generate(&self, _cancel: &CancelToken) -> anyhow::Result<PooledMint> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(PooledMint::Local(Keypair::new()))
+        }
+    }
+
+    /// Always the same mint
+    struct Repeat(Pubkey);
+
+    #[async_trait]
+    impl MintGenerator for Repeat {
+        async fn generate(&self, _cancel: &CancelToken) -> anyhow::Result<PooledMint> {
+            Ok(PooledMint::Remote(self.0))
+        }
+    }
+
+    struct Panicking;
+
+    #[async_trait]
+    impl MintGenerator for Panicking {
+        async fn generate(&self, _cancel: &CancelToken) -> anyhow::Result<PooledMint> {
+            panic!("generator bug");
+        }
+    }
+
+    /// Holds at most one saved mint. `save` and `mark_taken` hang while
+    /// `hang_save` and `hang` are set.
+    #[derive(Default)]
+    struct Store {
+        saved: Mutex<Option<Keypair>>,
+        hang_save: AtomicBool,
+        hang: AtomicBool,
+        saving: Mutex<Vec<Pubkey>>,
+        taken: Mutex<Vec<Pubkey>>,
+    }
+
+    impl Store {
+        fn with_saved(mint: Keypair) -> Self {
+            Self {
+                saved: Mutex::new(Some(mint)),
+                ..Default::default()
+            }
+        }
+    }
+
+    #[async_trait]
+    impl MintStore for Store {
+        async fn load(&self) -> anyhow::Result<Vec<PooledMint>> {
+            Ok(self
+                .saved
+                .lock()
+                .unwrap()
+                .take()
+                .map(PooledMint::Local)
+                .into_iter()
+                .collect())
+        }
+
+        async fn save(&self, mint: &PooledMint) -> anyhow::Result<()> {
+            self.saving.lock().unwrap().push(mint.pubkey());
+            while self.hang_save.load(Ordering::SeqCst) {
+                sleep(Duration::from_millis(5)).await;
+            }
+            Ok(())
+        }
+
+        async fn mark_taken(&self, mint: &Pubkey) -> anyhow::Result<()> {
+            if self.hang.load(Ordering::SeqCst) {
+                std::future::pending::<()>().await;
+            }
+            self.taken.lock().unwrap().push(*mint);
+            Ok(())
+        }
+    }
+
+    async fn wait_until(condition: impl Fn() -> bool) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !condition() {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("condition not reached");
+    }
+
+    #[tokio::test]
+    async fn refills_up_to_capacity() {
+        let generator = Arc::new(Counting::default());
+        let pool = MintPool::spawn(generator.clone(), 3);
+
+        wait_until(|| pool.len() == 3).await;
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(pool.len(), 3);
+        assert_eq!(generator.0.load(Ordering::SeqCst), 3);
+
+        pool.take().await.unwrap();
+        wait_until(|| pool.len() == 3).await;
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(generator.0.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn rejects_duplicate_mints() {
+        let mint = Keypair::new();
+        let pubkey = mint.pubkey();
+        let pool = MintPool::spawn_with_store(Repeat(pubkey), 2, Store::with_saved(mint))
+            .await
+            .unwrap();
+
+        wait_until(|| pool.last_error().is_some()).await;
+        assert_eq!(
+            pool.last_error().unwrap(),
+            format!("Duplicate mint {}", pubkey)
+        );
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.take().await.unwrap().pubkey(), pubkey);
+        assert!(pool.try_take().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn saves_mints_before_they_enter_the_pool() {
+        let store = Arc::new(Store {
+            hang_save: AtomicBool::new(true),
+            ..Default::default()
+        });
+        let pool = MintPool::spawn_with_store(RandomMint, 1, store.clone())
+            .await
+            .unwrap();
+
+        wait_until(|| store.saving.lock().unwrap().len() == 1).await;
+        sleep(Duration::from_millis(50)).await;
+        assert!(pool.is_empty());
+
+        store.hang_save.store(false, Ordering::SeqCst);
+        let mint = pool.take().await.unwrap();
+        assert_eq!(store.saving.lock().unwrap()[0], mint.pubkey());
+    }
+
+    #[tokio::test]
+    async fn cancelled_take_keeps_the_mint() {
+        let mint = Keypair::new();
+        let pubkey = mint.pubkey();
+        let store = Arc::new(Store {
+            hang: AtomicBool::new(true),
+            ..Store::with_saved(mint)
+        });
+        let pool = MintPool::spawn_with_store(Idle, 1, store.clone())
+            .await
+            .unwrap();
+
+        let cancelled = tokio::time::timeout(Duration::from_millis(50), pool.take()).await;
+        assert!(cancelled.is_err());
+        assert_eq!(pool.len(), 1);
+
+        store.hang.store(false, Ordering::SeqCst);
+        let taken = pool.try_take().await.unwrap().unwrap();
+        assert_eq!(taken.pubkey(), pubkey);
+        assert_eq!(*store.taken.lock().unwrap(), [pubkey]);
+        assert!(pool.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reserved_mints_count_toward_capacity() {
+        let store = Arc::new(Store {
+            hang: AtomicBool::new(true),
+            ..Store::with_saved(Keypair::new())
+        });
+        let generator = Arc::new(Counting::default());
+        let pool = MintPool::spawn_with_store(generator.clone(), 1, store.clone())
+            .await
+            .unwrap();
+
+        let cancelled = tokio::time::timeout(Duration::from_millis(50), pool.take()).await;
+        assert!(cancelled.is_err());
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(pool.len(), 1);
+        assert_eq!(generator.0.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn take_fails_once_the_refill_task_stops() {
+        let pool = MintPool::spawn(Panicking, 1);
+
+        let result = tokio::time::timeout(Duration::from_secs(5), pool.take())
+            .await
+            .expect("take did not return");
+        assert!(matches!(result, Err(MintPoolError::Closed)));
+    }
+}
@@ -0,0 +1,585 @@
+//! Local HTTP server imitating FURY API, for integration tests and CI that
+//! must run offline. Enabled by the `mock-server` feature.
+//!
+//! ```no_run
+//! # use fury_sdk::{api::Endpoint, mock_server::{MockFuryServer, MockResponse, StatusCode}, retry::RetryPolicy, sdk::BuyTokenRequest};
+//! # async fn run(request: BuyTokenRequest) -> anyhow::Result<()> {
+//! let server = MockFuryServer::start().await?;
+//! server.push_response(
+//!     Endpoint::BuyToken,
+//!     MockResponse::error(StatusCode::SERVICE_UNAVAILABLE, "Unavailable").with_delay(std::time::Duration::from_millis(200)),
+//! );
+//!
+//! // The 503 is retried, then the default response is served
+//! let fury = server.sdk()?.with_retry_policy(RetryPolicy::default());
+//! fury.buy_token(&request).await?;
+//! assert_eq!(server.requests_to(Endpoint::BuyToken).len(), 2);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Routes without a configured response answer like the real API. Buys and
+//! sells return one unsigned transaction per wallet, paid by that wallet,
+//! so they can be signed with the wallets' keypairs and sent back.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::{Bytes, Incoming},
+    header::{HeaderName, HeaderValue, CONTENT_TYPE},
+    server::conn::http1,
+    service::service_fn,
+    HeaderMap, Method, Request, Response,
+};
+use hyper_util::rt::TokioIo;
+use rand::RngCore;
+use serde_json::{json, Value};
+use solana_sdk::{
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+};
+use tokio::{net::TcpListener, task::JoinSet, time::sleep};
+
+pub use hyper::StatusCode;
+
+use crate::{
+    api::Endpoint,
+    sdk::{FuryError, FurySDK},
+    test_support::unsigned_transfer,
+    utils::{deserialize_transaction, serialize_transaction},
+};
+
+/// Wait after a failed `accept`, e.g. when out of file descriptors
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+const ENDPOINTS: [Endpoint; 12] = [
+    Endpoint::HealthCheck,
+    Endpoint::BuyToken,
+    Endpoint::SellToken,
+    Endpoint::SendTransactions,
+    Endpoint::TokenTransfer,
+    Endpoint::TokensCreate,
+    Endpoint::TokenBurn,
+    Endpoint::TokenCleaner,
+    Endpoint::AnalyticsPnl,
+    Endpoint::GenerateMint,
+    Endpoint::WalletsDistribute,
+    Endpoint::WalletsConsolidate,
+];
+
+/// A canned HTTP response
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: StatusCode,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    /// Time to wait before responding, e.g. to trigger client timeouts
+    pub delay: Duration,
+}
+
+impl MockResponse {
+    /// Empty response with `status`
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: String::new(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// 200 with a JSON body
+    pub fn json(body: Value) -> Self {
+        Self::new(StatusCode::OK)
+            .with_header(CONTENT_TYPE.as_str(), "application/json")
+            .with_body(body.to_string())
+    }
+
+    /// `status` with the error body FURY API returns, which the SDK turns
+    /// into `FuryError::ApiError`
+    pub fn error(status: StatusCode, message: &str) -> Self {
+        Self {
+            status,
+            ..Self::json(json!({ "success": false, "error": message }))
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// A request received by `MockFuryServer`
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    /// `None` for paths that are not a FURY API route
+    pub endpoint: Option<Endpoint>,
+    pub method: Method,
+    pub path: String,
+    pub query: Option<String>,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl RecordedRequest {
+    /// Body parsed as JSON, `None` if it is empty or not JSON
+    pub fn json(&self) -> Option<Value> {
+        serde_json::from_slice(&self.body).ok()
+    }
+}
+
+#[derive(Default)]
+struct Route {
+    /// Served once each, before `fixed`
+    queued: VecDeque<MockResponse>,
+    /// Served on every call once the queue is empty. `None` answers like the
+    /// real API.
+    fixed: Option<MockResponse>,
+    /// Added to every response of the route
+    delay: Duration,
+}
+
+#[derive(Default)]
+struct State {
+    routes: HashMap<Endpoint, Route>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// Imitation of every route `FurySDK` calls, served on a random local port.
+///
+/// Responses can be configured per route, and every request is recorded.
+/// Dropping the server shuts it down.
+pub struct MockFuryServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl MockFuryServer {
+    /// Binds to `127.0.0.1` on a free port and starts serving. Must be
+    /// called within a tokio runtime.
+    pub async fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State::default()));
+        let task = tokio::spawn(serve(listener, state.clone()));
+        Ok(Self { addr, state, task })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// API root to pass to `FurySDK::new_with_base_url` or
+    /// `FurySDKBuilder::base_url`. The health check is served from the
+    /// parent, like the real API.
+    pub fn base_url(&self) -> String {
+        format!("http://{}/api/", self.addr)
+    }
+
+    /// `FurySDK` pointed at this server
+    pub fn sdk(&self) -> Result<FurySDK, FuryError> {
+        FurySDK::new_with_base_url(reqwest::Client::new(), &self.base_url())
+    }
+
+    /// Serves `response` on every call to `endpoint`, once responses queued
+    /// with `push_response` run out
+    pub fn set_response(&self, endpoint: Endpoint, response: MockResponse) {
+        self.route(endpoint, |route| route.fixed = Some(response));
+    }
+
+    /// Serves `response` on the next call to `endpoint` only
+    pub fn push_response(&self, endpoint: Endpoint, response: MockResponse) {
+        self.route(endpoint, |route| route.queued.push_back(response));
+    }
+
+    /// Delays every response of `endpoint`, including the default ones
+    pub fn set_delay(&self, endpoint: Endpoint, delay: Duration) {
+        self.route(endpoint, |route| route.delay = delay);
+    }
+
+    /// Goes back to answering `endpoint` like the real API
+    pub fn reset(&self, endpoint: Endpoint) {
+        self.state.lock().unwrap().routes.remove(&endpoint);
+    }
+
+    /// Every request received so far, in order
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    pub fn requests_to(&self, endpoint: Endpoint) -> Vec<RecordedRequest> {
+        self.state
+            .lock()
+            .unwrap()
+            .requests
+            .iter()
+            .filter(|request| request.endpoint == Some(endpoint))
+            .cloned()
+            .collect()
+    }
+
+    pub fn clear_requests(&self) {
+        self.state.lock().unwrap().requests.clear();
+    }
+
+    fn route(&self, endpoint: Endpoint, update: impl FnOnce(&mut Route)) {
+        update(
+            self.state
+                .lock()
+                .unwrap()
+                .routes
+                .entry(endpoint)
+                .or_default(),
+        );
+    }
+}
+
+impl Drop for MockFuryServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Accepts connections until aborted. Open connections live in the
+/// `JoinSet`, so they are closed along with the listener.
+async fn serve(listener: TcpListener, state: Arc<Mutex<State>>) {
+    let mut connections = JoinSet::new();
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(_) => {
+                sleep(ACCEPT_RETRY_DELAY).await;
+                continue;
+            }
+        };
+        while connections.try_join_next().is_some() {}
+
+        let state = state.clone();
+        connections.spawn(async move {
+            let service = service_fn(move |request| handle(state.clone(), request));
+            let _ = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await;
+        });
+    }
+}
+
+async fn handle(
+    state: Arc<Mutex<State>>,
+    request: Request<Incoming>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let (parts, body) = request.into_parts();
+    let body = match body.collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(e) => {
+            return Ok(to_response(MockResponse::error(
+                StatusCode::BAD_REQUEST,
+                &e.to_string(),
+            )))
+        }
+    };
+    let endpoint = route_endpoint(parts.uri.path());
+    let recorded = RecordedRequest {
+        endpoint,
+        method: parts.method,
+        path: parts.uri.path().to_string(),
+        query: parts.uri.query().map(str::to_string),
+        headers: parts.headers,
+        body,
+    };
+
+    let (configured, route_delay) = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(recorded.clone());
+        match endpoint.and_then(|endpoint| state.routes.get_mut(&endpoint)) {
+            Some(route) => (
+                route.queued.pop_front().or_else(|| route.fixed.clone()),
+                route.delay,
+            ),
+            None => (None, Duration::ZERO),
+        }
+    };
+    let response = match (configured, endpoint) {
+        (Some(response), _) => response,
+        (None, Some(endpoint)) => default_response(endpoint, &recorded),
+        (None, None) => MockResponse::error(StatusCode::NOT_FOUND, "Not found"),
+    };
+
+    let delay = route_delay + response.delay;
+    if !delay.is_zero() {
+        sleep(delay).await;
+    }
+    Ok(to_response(response))
+}
+
+fn to_response(mock: MockResponse) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from(mock.body)));
+    *response.status_mut() = mock.status;
+    for (name, value) in mock.headers {
+        if let (Ok(name), Ok(value)) = (HeaderName::try_from(name), HeaderValue::try_from(value)) {
+            response.headers_mut().append(name, value);
+        }
+    }
+    response
+}
+
+/// The health check sits next to the API root, every other route under it
+fn route_endpoint(path: &str) -> Option<Endpoint> {
+    ENDPOINTS.into_iter().find(|endpoint| match endpoint {
+        Endpoint::HealthCheck => path == "/health",
+        _ => path.strip_prefix("/api/") == Some(endpoint.path()),
+    })
+}
+
+/// Response shaped like the real API's, built from the request
+fn default_response(endpoint: Endpoint, request: &RecordedRequest) -> MockResponse {
+    let body = request.json().unwrap_or(Value::Null);
+    let response = match endpoint {
+        Endpoint::HealthCheck => json!({ "status": "ok" }),
+        Endpoint::BuyToken | Endpoint::SellToken => json!({
+            "success": true,
+            "transactions": pubkeys(&body["walletAddresses"])
+                .iter()
+                .map(placeholder_transaction)
+                .collect::<Vec<_>>(),
+        }),
+        Endpoint::SendTransactions => send_response(&body),
+        Endpoint::TokenTransfer => json!({
+            "success": true,
+            "data": {
+                "transaction": placeholder_transaction(&pubkey(&body["senderPublicKey"])),
+                "blockhash": Hash::new_unique().to_string(),
+                "last_valid_block_height": 0,
+                "transfer_type": "token",
+            },
+        }),
+        Endpoint::TokenBurn => json!({
+            "success": true,
+            "data": {
+                "transaction": placeholder_transaction(&pubkey(&body["walletPublicKey"])),
+                "blockhash": Hash::new_unique().to_string(),
+                "amount": body["amount"].as_f64().unwrap_or_default(),
+                "decimals": 6,
+                "token_mint": pubkey(&body["tokenAddress"]).to_string(),
+                "associated_token_address": Pubkey::new_unique().to_string(),
+            },
+        }),
+        Endpoint::AnalyticsPnl => {
+            let data = body["addresses"]
+                .as_str()
+                .unwrap_or_default()
+                .split(',')
+                .filter(|address| !address.is_empty())
+                .map(|address| {
+                    let details = json!({ "profit": 0.0, "timestamp": "" });
+                    (address.to_string(), details)
+                })
+                .collect::<serde_json::Map<_, _>>();
+            json!({ "success": true, "data": data })
+        }
+        Endpoint::GenerateMint => json!({ "pubkey": Keypair::new().pubkey().to_string() }),
+        Endpoint::TokensCreate
+        | Endpoint::TokenCleaner
+        | Endpoint::WalletsDistribute
+        | Endpoint::WalletsConsolidate => json!({ "success": true, "transactions": [] }),
+    };
+    MockResponse::json(response)
+}
+
+/// RPC delivery echoes the transactions' signatures, Jito delivery returns
+/// a random bundle id
+fn send_response(body: &Value) -> Value {
+    if body["use_rpc"].as_bool() == Some(true) {
+        let signatures = body["transactions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .map(|(index, transaction)| {
+                deserialize_transaction(index, transaction.as_str().unwrap_or_default())
+                    .ok()
+                    .and_then(|transaction| transaction.signatures.first().copied())
+                    .unwrap_or_else(Signature::new_unique)
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        json!({ "success": true, "result": { "rpc": signatures } })
+    } else {
+        let mut bundle_id = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bundle_id);
+        let bundle_id = bundle_id
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        json!({ "success": true, "result": { "jito": bundle_id } })
+    }
+}
+
+fn pubkey(value: &Value) -> Pubkey {
+    value
+        .as_str()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_else(Pubkey::new_unique)
+}
+
+fn pubkeys(value: &Value) -> Vec<Pubkey> {
+    value.as_array().into_iter().flatten().map(pubkey).collect()
+}
+
+/// Base58 unsigned transaction paid by `payer`: an empty transfer to itself
+fn placeholder_transaction(payer: &Pubkey) -> String {
+    serialize_transaction(0, &unsigned_transfer(payer, payer, payer, 0))
+        .expect("transaction serializes")
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signature::Keypair;
+
+    use super::*;
+    use crate::{
+        retry::RetryPolicy,
+        sdk::{Delivery, SendOutcome},
+        test_support::buy_request,
+    };
+
+    #[tokio::test]
+    async fn buys_signs_and_sends() {
+        let server = MockFuryServer::start().await.unwrap();
+        let fury = server.sdk().unwrap();
+        let (first, second) = (Keypair::new(), Keypair::new());
+
+        let response = fury
+            .buy_token(&buy_request(&[first.pubkey(), second.pubkey()]))
+            .await
+            .unwrap();
+        let signed = response.transactions.sign(&[&first, &second]).unwrap();
+        let outcome = fury
+            .send_transactions(&signed, Delivery::Rpc)
+            .await
+            .unwrap();
+
+        let SendOutcome::Rpc(outcome) = outcome else {
+            panic!("expected an RPC send outcome");
+        };
+        let signatures: Vec<_> = signed.iter().map(|tx| tx.signatures[0]).collect();
+        assert_eq!(outcome.result.rpc, signatures);
+        let buys = server.requests_to(Endpoint::BuyToken);
+        assert_eq!(buys.len(), 1);
+        assert_eq!(
+            buys[0].json().unwrap()["walletAddresses"][1],
+            second.pubkey().to_string()
+        );
+        assert_eq!(server.requests_to(Endpoint::SendTransactions).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn retries_unavailable_then_succeeds() {
+        let server = MockFuryServer::start().await.unwrap();
+        server.push_response(
+            Endpoint::BuyToken,
+            MockResponse::error(StatusCode::SERVICE_UNAVAILABLE, "Unavailable"),
+        );
+        let fury = server.sdk().unwrap().with_retry_policy(RetryPolicy {
+            base_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        });
+
+        let wallet = Keypair::new();
+        let response = fury
+            .buy_token(&buy_request(&[wallet.pubkey()]))
+            .await
+            .unwrap();
+
+        assert_eq!(response.transactions.len(), 1);
+        assert_eq!(server.requests_to(Endpoint::BuyToken).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn surfaces_api_errors() {
+        let server = MockFuryServer::start().await.unwrap();
+        server.set_response(
+            Endpoint::BuyToken,
+            MockResponse::error(StatusCode::BAD_REQUEST, "Invalid token"),
+        );
+        let fury = server.sdk().unwrap().with_retry_policy(RetryPolicy::none());
+
+        let wallet = Keypair::new();
+        let error = fury
+            .buy_token(&buy_request(&[wallet.pubkey()]))
+            .await
+            .unwrap_err();
+
+        let FuryError::ApiError(error) = error else {
+            panic!("expected an API error, got {:?}", error);
+        };
+        assert_eq!(error.status, Some(StatusCode::BAD_REQUEST));
+        assert_eq!(error.error.as_deref(), Some("Invalid token"));
+    }
+
+    #[tokio::test]
+    async fn surfaces_non_json_errors() {
+        let server = MockFuryServer::start().await.unwrap();
+        let html = "<html><body><h1>502 Bad Gateway</h1></body></html>";
+        server.push_response(
+            Endpoint::BuyToken,
+            MockResponse::new(StatusCode::BAD_GATEWAY)
+                .with_header(CONTENT_TYPE.as_str(), "text/html")
+                .with_body(html),
+        );
+        server.push_response(
+            Endpoint::BuyToken,
+            MockResponse::new(StatusCode::TOO_MANY_REQUESTS),
+        );
+        server.push_response(
+            Endpoint::BuyToken,
+            MockResponse::error(StatusCode::BAD_REQUEST, "Invalid token"),
+        );
+        let fury = server.sdk().unwrap().with_retry_policy(RetryPolicy::none());
+        let request = buy_request(&[Keypair::new().pubkey()]);
+
+        let error = fury.buy_token(&request).await.unwrap_err();
+        let FuryError::HttpError(response) = &error else {
+            panic!("expected an HTTP error, got {:?}", error);
+        };
+        assert_eq!(response.body, html);
+        assert_eq!(error.status(), Some(StatusCode::BAD_GATEWAY));
+        assert!(error.is_retryable());
+        assert!(!error.is_rate_limited());
+
+        let error = fury.buy_token(&request).await.unwrap_err();
+        let FuryError::HttpError(response) = &error else {
+            panic!("expected an HTTP error, got {:?}", error);
+        };
+        assert!(response.body.is_empty());
+        assert_eq!(error.status(), Some(StatusCode::TOO_MANY_REQUESTS));
+        assert!(error.is_retryable());
+        assert!(error.is_rate_limited());
+
+        let error = fury.buy_token(&request).await.unwrap_err();
+        assert_eq!(error.status(), Some(StatusCode::BAD_REQUEST));
+        assert!(!error.is_retryable());
+    }
+}
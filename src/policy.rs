@@ -0,0 +1,794 @@
+//! Checks applied to transactions returned by FURY API before they are
+//! signed.
+//!
+//! The checks look at top-level instructions only. When destinations are
+//! restricted, System and SPL Token instructions that are not decoded in
+//! detail are rejected, as their effect cannot be checked. Transfers performed by
+//! other programs through CPI (e.g. the SOL spent inside a swap) are not
+//! visible here; restrict those programs with `allowed_programs`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+};
+
+use solana_sdk::{
+    pubkey, pubkey::Pubkey, sanitize::SanitizeError, transaction::VersionedTransaction,
+};
+
+use solana_system_interface::program as system_program;
+
+use crate::{
+    amount::Lamports,
+    inspect::{
+        associated_token_address, inspect_transaction, DecodedInstruction,
+        SystemInstructionSummary, TokenInstructionSummary, NATIVE_MINT, TOKEN_2022_PROGRAM_ID,
+        TOKEN_PROGRAM_ID,
+    },
+};
+
+/// Jito block engine tip accounts
+pub const JITO_TIP_ACCOUNTS: [Pubkey; 8] = [
+    pubkey!("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5"),
+    pubkey!("HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe"),
+    pubkey!("Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY"),
+    pubkey!("ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49"),
+    pubkey!("DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh"),
+    pubkey!("ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt"),
+    pubkey!("DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL"),
+    pubkey!("3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT"),
+];
+
+/// A rule broken by a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyViolation {
+    /// An instruction invokes a program outside the allow list
+    ProgramNotAllowed { program_id: Pubkey },
+    /// A signer sends more SOL than allowed
+    OutflowExceeded {
+        signer: Pubkey,
+        lamports: u64,
+        limit: u64,
+    },
+    /// SOL or tokens are sent to an account outside the allow list
+    DestinationNotAllowed { destination: Pubkey },
+    /// An SPL Token `Approve` or `ApproveChecked` to a delegate outside the
+    /// allow list
+    DelegateNotAllowed { delegate: Pubkey },
+    /// A System `Assign` hands a signer account to another program
+    Assign { account: Pubkey, owner: Pubkey },
+    /// A System or SPL Token instruction that is not decoded in detail, so
+    /// its destinations cannot be checked
+    UnrecognizedInstruction {
+        instruction: usize,
+        program_id: Pubkey,
+    },
+    /// The total tip paid to Jito tip accounts is above the limit
+    JitoTipExceeded { lamports: u64, limit: u64 },
+    /// An SPL Token `SetAuthority` instruction
+    SetAuthority { account: Option<Pubkey> },
+    /// An SPL Token `CloseAccount` that sends the rent elsewhere than a
+    /// signer or allowed destination
+    CloseAccount {
+        account: Option<Pubkey>,
+        destination: Option<Pubkey>,
+    },
+    /// A destination is loaded from an address lookup table and cannot be
+    /// checked offline
+    UnresolvedAccount { instruction: usize },
+    /// The message references accounts it does not contain
+    Malformed(SanitizeError),
+}
+
+impl Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyViolation::ProgramNotAllowed { program_id } => {
+                write!(f, "Program {} is not allowed", program_id)
+            }
+            PolicyViolation::OutflowExceeded {
+                signer,
+                lamports,
+                limit,
+            } => write!(
+                f,
+                "Signer {} sends {} lamports, limit is {}",
+                signer, lamports, limit
+            ),
+            PolicyViolation::DestinationNotAllowed { destination } => {
+                write!(f, "Destination {} is not allowed", destination)
+            }
+            PolicyViolation::DelegateNotAllowed { delegate } => {
+                write!(f, "Delegate {} is not allowed", delegate)
+            }
+            PolicyViolation::Assign { account, owner } => {
+                write!(f, "Unexpected Assign of {} to {}", account, owner)
+            }
+            PolicyViolation::UnrecognizedInstruction {
+                instruction,
+                program_id,
+            } => write!(
+                f,
+                "Instruction {} of {} cannot be checked",
+                instruction, program_id
+            ),
+            PolicyViolation::JitoTipExceeded { lamports, limit } => {
+                write!(f, "Jito tip of {} lamports exceeds {}", lamports, limit)
+            }
+            PolicyViolation::SetAuthority { account } => {
+                write!(f, "Unexpected SetAuthority on {}", display_option(account))
+            }
+            PolicyViolation::CloseAccount {
+                account,
+                destination,
+            } => write!(
+                f,
+                "Unexpected CloseAccount of {} to {}",
+                display_option(account),
+                display_option(destination)
+            ),
+            PolicyViolation::UnresolvedAccount { instruction } => write!(
+                f,
+                "Instruction {} uses a lookup table account that cannot be checked",
+                instruction
+            ),
+            PolicyViolation::Malformed(e) => write!(f, "Malformed transaction: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
+
+fn display_option(key: &Option<Pubkey>) -> String {
+    key.map(|key| key.to_string())
+        .unwrap_or_else(|| "<lookup table account>".to_string())
+}
+
+/// A check run on every transaction before it is signed
+pub trait TransactionPolicy: Send + Sync {
+    fn check(&self, transaction: &VersionedTransaction) -> Result<(), PolicyViolation>;
+}
+
+/// Configurable policy covering the common ways a malicious or buggy backend
+/// could drain a wallet. Every limit is disabled when left as `None`.
+///
+/// ```
+/// # use fury_sdk::{amount::Lamports, policy::SigningPolicy};
+/// let policy = SigningPolicy {
+///     max_outflow_per_signer: Some(Lamports::new(100_000_000)),
+///     max_jito_tip: Some(Lamports::new(1_000_000)),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub struct SigningPolicy {
+    /// Programs that may be invoked. The System, Compute Budget, SPL Token
+    /// and Associated Token programs have to be listed too.
+    pub allowed_programs: Option<HashSet<Pubkey>>,
+    /// Maximum SOL a single signer may send through System instructions,
+    /// Jito tips included
+    pub max_outflow_per_signer: Option<Lamports>,
+    /// Accounts SOL and tokens may be sent to, and token delegates. Transaction
+    /// signers, their wrapped SOL accounts and Jito tip accounts are always
+    /// allowed.
+    pub allowed_destinations: Option<HashSet<Pubkey>>,
+    /// Maximum total tip paid to `jito_tip_accounts`
+    pub max_jito_tip: Option<Lamports>,
+    pub jito_tip_accounts: Vec<Pubkey>,
+    /// Allow SPL Token `SetAuthority` instructions
+    pub allow_set_authority: bool,
+    /// Allow SPL Token `CloseAccount` to any destination. Closing to a signer
+    /// or allowed destination is always accepted.
+    pub allow_close_account: bool,
+    /// Allow System `Assign` of a signer account to another program
+    pub allow_assign: bool,
+}
+
+impl Default for SigningPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_programs: None,
+            max_outflow_per_signer: None,
+            allowed_destinations: None,
+            max_jito_tip: None,
+            jito_tip_accounts: JITO_TIP_ACCOUNTS.to_vec(),
+            allow_set_authority: false,
+            allow_close_account: false,
+            allow_assign: false,
+        }
+    }
+}
+
+impl TransactionPolicy for SigningPolicy {
+    fn check(&self, transaction: &VersionedTransaction) -> Result<(), PolicyViolation> {
+        let summary = inspect_transaction(transaction).map_err(PolicyViolation::Malformed)?;
+        let signers = &summary.signers;
+
+        let mut outflows: HashMap<Pubkey, u64> = HashMap::new();
+        let mut jito_tip: u64 = 0;
+
+        for (position, instruction) in summary.instructions.iter().enumerate() {
+            let program_id = instruction.program_id;
+            if let Some(allowed_programs) = &self.allowed_programs {
+                if !allowed_programs.contains(&program_id) {
+                    return Err(PolicyViolation::ProgramNotAllowed { program_id });
+                }
+            }
+            let unresolved = PolicyViolation::UnresolvedAccount {
+                instruction: position,
+            };
+
+            match &instruction.decoded {
+                // A seeded transfer debits an account derived from `base`,
+                // which cannot sign, so the outflow is charged to `base`
+                DecodedInstruction::System(
+                    SystemInstructionSummary::Transfer {
+                        from: payer,
+                        to,
+                        lamports,
+                    }
+                    | SystemInstructionSummary::TransferWithSeed {
+                        base: payer,
+                        to,
+                        lamports,
+                        ..
+                    }
+                    | SystemInstructionSummary::CreateAccount {
+                        from: payer,
+                        to,
+                        lamports,
+                        ..
+                    }
+                    | SystemInstructionSummary::CreateAccountWithSeed {
+                        from: payer,
+                        to,
+                        lamports,
+                        ..
+                    },
+                ) => {
+                    let lamports = lamports.lamports();
+                    if let Some(payer) = payer.pubkey().filter(|payer| signers.contains(payer)) {
+                        let outflow = outflows.entry(*payer).or_default();
+                        *outflow = outflow.saturating_add(lamports);
+                    }
+                    match to.pubkey() {
+                        Some(to) if self.jito_tip_accounts.contains(to) => {
+                            jito_tip = jito_tip.saturating_add(lamports);
+                        }
+                        Some(to) => self.check_destination(to, signers)?,
+                        None if self.allowed_destinations.is_some() => return Err(unresolved),
+                        None => {}
+                    }
+                }
+                DecodedInstruction::System(SystemInstructionSummary::Assign { account, owner })
+                    if !self.allow_assign =>
+                {
+                    // Lookup table accounts cannot be signers
+                    if let Some(account) = account.pubkey().filter(|a| signers.contains(a)) {
+                        return Err(PolicyViolation::Assign {
+                            account: *account,
+                            owner: *owner,
+                        });
+                    }
+                }
+                DecodedInstruction::Token(TokenInstructionSummary::SetAuthority {
+                    account,
+                    ..
+                }) if !self.allow_set_authority => {
+                    return Err(PolicyViolation::SetAuthority {
+                        account: account.pubkey().copied(),
+                    });
+                }
+                DecodedInstruction::Token(TokenInstructionSummary::CloseAccount {
+                    account,
+                    destination,
+                    ..
+                }) if !self.allow_close_account => {
+                    let allowed = destination.pubkey().is_some_and(|destination| {
+                        signers.contains(destination) || self.is_allowed_destination(destination)
+                    });
+                    if !allowed {
+                        return Err(PolicyViolation::CloseAccount {
+                            account: account.pubkey().copied(),
+                            destination: destination.pubkey().copied(),
+                        });
+                    }
+                }
+                DecodedInstruction::Token(TokenInstructionSummary::Transfer {
+                    destination,
+                    ..
+                }) if self.allowed_destinations.is_some() => {
+                    let destination = destination.pubkey().ok_or(unresolved)?;
+                    self.check_token_destination(destination, None, &program_id, signers)?;
+                }
+                DecodedInstruction::Token(TokenInstructionSummary::TransferChecked {
+                    mint,
+                    destination,
+                    ..
+                }) if self.allowed_destinations.is_some() => {
+                    let destination = destination.pubkey().ok_or(unresolved)?;
+                    self.check_token_destination(
+                        destination,
+                        mint.pubkey().copied(),
+                        &program_id,
+                        signers,
+                    )?;
+                }
+                DecodedInstruction::Token(TokenInstructionSummary::Approve {
+                    delegate, ..
+                }) if self.allowed_destinations.is_some() => {
+                    let delegate = delegate.pubkey().ok_or(unresolved)?;
+                    self.check_destination(delegate, signers).map_err(|_| {
+                        PolicyViolation::DelegateNotAllowed {
+                            delegate: *delegate,
+                        }
+                    })?;
+                }
+                // Moves no funds, needed by durable nonce transactions
+                DecodedInstruction::System(SystemInstructionSummary::Other { name })
+                    if name == "AdvanceNonceAccount" => {}
+                DecodedInstruction::System(SystemInstructionSummary::Other { .. })
+                | DecodedInstruction::Token(TokenInstructionSummary::Other { .. })
+                    if self.allowed_destinations.is_some() =>
+                {
+                    return Err(PolicyViolation::UnrecognizedInstruction {
+                        instruction: position,
+                        program_id,
+                    });
+                }
+                DecodedInstruction::Unknown { .. }
+                    if self.allowed_destinations.is_some()
+                        && [system_program::ID, TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID]
+                            .contains(&program_id) =>
+                {
+                    return Err(PolicyViolation::UnrecognizedInstruction {
+                        instruction: position,
+                        program_id,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(limit) = self.max_jito_tip {
+            if jito_tip > limit.lamports() {
+                return Err(PolicyViolation::JitoTipExceeded {
+                    lamports: jito_tip,
+                    limit: limit.lamports(),
+                });
+            }
+        }
+        if let Some(limit) = self.max_outflow_per_signer {
+            if let Some((signer, lamports)) = outflows
+                .into_iter()
+                .find(|(_, lamports)| *lamports > limit.lamports())
+            {
+                return Err(PolicyViolation::OutflowExceeded {
+                    signer,
+                    lamports,
+                    limit: limit.lamports(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SigningPolicy {
+    fn is_allowed_destination(&self, destination: &Pubkey) -> bool {
+        self.allowed_destinations
+            .as_ref()
+            .is_some_and(|allowed| allowed.contains(destination))
+    }
+
+    fn check_destination(
+        &self,
+        destination: &Pubkey,
+        signers: &[Pubkey],
+    ) -> Result<(), PolicyViolation> {
+        if self.allowed_destinations.is_none()
+            || self.is_allowed_destination(destination)
+            || signers.contains(destination)
+            || signers.iter().any(|signer| {
+                [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID]
+                    .iter()
+                    .any(|program| {
+                        associated_token_address(signer, &NATIVE_MINT, program) == *destination
+                    })
+            })
+        {
+            return Ok(());
+        }
+        Err(PolicyViolation::DestinationNotAllowed {
+            destination: *destination,
+        })
+    }
+
+    fn check_token_destination(
+        &self,
+        destination: &Pubkey,
+        mint: Option<Pubkey>,
+        token_program: &Pubkey,
+        signers: &[Pubkey],
+    ) -> Result<(), PolicyViolation> {
+        if self.is_allowed_destination(destination) {
+            return Ok(());
+        }
+        if let (Some(mint), Some(allowed)) = (mint, &self.allowed_destinations) {
+            let owners = signers.iter().chain(allowed.iter());
+            if owners
+                .into_iter()
+                .any(|owner| associated_token_address(owner, &mint, token_program) == *destination)
+            {
+                return Ok(());
+            }
+        }
+        Err(PolicyViolation::DestinationNotAllowed {
+            destination: *destination,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_system_interface::instruction as system_instruction;
+
+    use super::*;
+    use crate::test_support::unsigned_transaction as transaction;
+
+    fn restricted(allowed: &[Pubkey]) -> SigningPolicy {
+        SigningPolicy {
+            allowed_destinations: Some(allowed.iter().copied().collect()),
+            ..Default::default()
+        }
+    }
+
+    fn token_instruction(
+        program_id: Pubkey,
+        accounts: &[(Pubkey, bool)],
+        data: Vec<u8>,
+    ) -> Instruction {
+        Instruction {
+            program_id,
+            accounts: accounts
+                .iter()
+                .map(|(key, signer)| AccountMeta::new(*key, *signer))
+                .collect(),
+            data,
+        }
+    }
+
+    fn approve(source: Pubkey, delegate: Pubkey, owner: Pubkey) -> Instruction {
+        let mut data = vec![4];
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        token_instruction(
+            TOKEN_PROGRAM_ID,
+            &[(source, false), (delegate, false), (owner, true)],
+            data,
+        )
+    }
+
+    fn approve_checked(
+        source: Pubkey,
+        mint: Pubkey,
+        delegate: Pubkey,
+        owner: Pubkey,
+    ) -> Instruction {
+        let mut data = vec![13];
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        data.push(6);
+        token_instruction(
+            TOKEN_PROGRAM_ID,
+            &[
+                (source, false),
+                (mint, false),
+                (delegate, false),
+                (owner, true),
+            ],
+            data,
+        )
+    }
+
+    fn set_authority(account: Pubkey, owner: Pubkey) -> Instruction {
+        // AccountOwner, new authority None
+        token_instruction(
+            TOKEN_PROGRAM_ID,
+            &[(account, false), (owner, true)],
+            vec![6, 2, 0],
+        )
+    }
+
+    fn close_account(account: Pubkey, destination: Pubkey, owner: Pubkey) -> Instruction {
+        token_instruction(
+            TOKEN_PROGRAM_ID,
+            &[(account, false), (destination, false), (owner, true)],
+            vec![9],
+        )
+    }
+
+    fn max_outflow(lamports: u64) -> SigningPolicy {
+        SigningPolicy {
+            max_outflow_per_signer: Some(Lamports::new(lamports)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn limits_outflow_summed_across_instructions() {
+        let (owner, to) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let transfers = |lamports: &[u64]| {
+            let instructions: Vec<_> = lamports
+                .iter()
+                .map(|lamports| system_instruction::transfer(&owner, &to, *lamports))
+                .collect();
+            transaction(&owner, &instructions)
+        };
+        let policy = max_outflow(100);
+
+        assert_eq!(policy.check(&transfers(&[60, 40])), Ok(()));
+        assert_eq!(
+            policy.check(&transfers(&[60, 41])),
+            Err(PolicyViolation::OutflowExceeded {
+                signer: owner,
+                lamports: 101,
+                limit: 100
+            })
+        );
+    }
+
+    #[test]
+    fn charges_seeded_outflow_to_the_signer() {
+        let (owner, to, program) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let derived = Pubkey::create_with_seed(&owner, "seed", &program).unwrap();
+        let policy = max_outflow(100);
+        let transfer_with_seed = |lamports| {
+            system_instruction::transfer_with_seed(
+                &derived,
+                &owner,
+                "seed".to_string(),
+                &program,
+                &to,
+                lamports,
+            )
+        };
+        let create_with_seed = |lamports| {
+            system_instruction::create_account_with_seed(
+                &owner, &derived, &owner, "seed", lamports, 0, &program,
+            )
+        };
+
+        for instruction in [transfer_with_seed(100), create_with_seed(100)] {
+            assert_eq!(policy.check(&transaction(&owner, &[instruction])), Ok(()));
+        }
+        for instruction in [transfer_with_seed(101), create_with_seed(101)] {
+            assert_eq!(
+                policy.check(&transaction(&owner, &[instruction])),
+                Err(PolicyViolation::OutflowExceeded {
+                    signer: owner,
+                    lamports: 101,
+                    limit: 100
+                })
+            );
+        }
+        let mixed = transaction(
+            &owner,
+            &[
+                system_instruction::transfer(&owner, &to, 50),
+                transfer_with_seed(30),
+                create_with_seed(30),
+            ],
+        );
+        assert_eq!(
+            policy.check(&mixed),
+            Err(PolicyViolation::OutflowExceeded {
+                signer: owner,
+                lamports: 110,
+                limit: 100
+            })
+        );
+    }
+
+    #[test]
+    fn limits_jito_tip() {
+        let owner = Pubkey::new_unique();
+        let policy = SigningPolicy {
+            max_jito_tip: Some(Lamports::new(1_000)),
+            ..Default::default()
+        };
+        let tips = |lamports: u64| {
+            transaction(
+                &owner,
+                &[
+                    system_instruction::transfer(&owner, &JITO_TIP_ACCOUNTS[0], lamports),
+                    system_instruction::transfer(&owner, &JITO_TIP_ACCOUNTS[1], 500),
+                    system_instruction::transfer(&owner, &Pubkey::new_unique(), 5_000),
+                ],
+            )
+        };
+
+        assert_eq!(policy.check(&tips(500)), Ok(()));
+        assert_eq!(
+            policy.check(&tips(501)),
+            Err(PolicyViolation::JitoTipExceeded {
+                lamports: 1_001,
+                limit: 1_000
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_set_authority() {
+        let (owner, account) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let set_authority = transaction(&owner, &[set_authority(account, owner)]);
+
+        assert_eq!(
+            SigningPolicy::default().check(&set_authority),
+            Err(PolicyViolation::SetAuthority {
+                account: Some(account)
+            })
+        );
+        let policy = SigningPolicy {
+            allow_set_authority: true,
+            ..Default::default()
+        };
+        assert_eq!(policy.check(&set_authority), Ok(()));
+    }
+
+    #[test]
+    fn rejects_close_account_to_unknown_destination() {
+        let (owner, account, attacker, trusted) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let policy = restricted(&[trusted]);
+        let to_attacker = transaction(&owner, &[close_account(account, attacker, owner)]);
+
+        assert_eq!(
+            policy.check(&to_attacker),
+            Err(PolicyViolation::CloseAccount {
+                account: Some(account),
+                destination: Some(attacker)
+            })
+        );
+        for destination in [owner, trusted] {
+            let close = transaction(&owner, &[close_account(account, destination, owner)]);
+            assert_eq!(policy.check(&close), Ok(()));
+        }
+        let policy = SigningPolicy {
+            allow_close_account: true,
+            ..policy
+        };
+        assert_eq!(policy.check(&to_attacker), Ok(()));
+    }
+
+    #[test]
+    fn rejects_programs_outside_allow_list() {
+        let (owner, account) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let unknown_program = Pubkey::new_unique();
+        let policy = SigningPolicy {
+            allowed_programs: Some([system_program::ID].into_iter().collect()),
+            ..Default::default()
+        };
+        let transfer = system_instruction::transfer(&owner, &account, 1);
+
+        assert_eq!(
+            policy.check(&transaction(&owner, std::slice::from_ref(&transfer))),
+            Ok(())
+        );
+        let call = Instruction::new_with_bytes(unknown_program, &[1], vec![]);
+        assert_eq!(
+            policy.check(&transaction(&owner, &[transfer, call])),
+            Err(PolicyViolation::ProgramNotAllowed {
+                program_id: unknown_program
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_approve_to_unknown_delegate() {
+        let (owner, source, mint) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let (attacker, trusted) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let policy = restricted(&[trusted]);
+
+        for instruction in [
+            approve(source, attacker, owner),
+            approve_checked(source, mint, attacker, owner),
+        ] {
+            assert_eq!(
+                policy.check(&transaction(&owner, &[instruction])),
+                Err(PolicyViolation::DelegateNotAllowed { delegate: attacker })
+            );
+        }
+        for instruction in [
+            approve(source, trusted, owner),
+            approve_checked(source, mint, trusted, owner),
+        ] {
+            assert_eq!(policy.check(&transaction(&owner, &[instruction])), Ok(()));
+        }
+    }
+
+    #[test]
+    fn rejects_assign_of_signer() {
+        let (owner, program) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let assign = transaction(&owner, &[system_instruction::assign(&owner, &program)]);
+
+        assert_eq!(
+            SigningPolicy::default().check(&assign),
+            Err(PolicyViolation::Assign {
+                account: owner,
+                owner: program
+            })
+        );
+        let policy = SigningPolicy {
+            allow_assign: true,
+            ..Default::default()
+        };
+        assert_eq!(policy.check(&assign), Ok(()));
+    }
+
+    #[test]
+    fn rejects_unrecognized_token_instruction_when_restricted() {
+        let (owner, source, mint, attacker) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        // Token-2022 TransferCheckedWithFee
+        let mut data = vec![26, 1];
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        data.push(6);
+        data.extend_from_slice(&10u64.to_le_bytes());
+        let transfer = token_instruction(
+            TOKEN_2022_PROGRAM_ID,
+            &[
+                (source, false),
+                (mint, false),
+                (attacker, false),
+                (owner, true),
+            ],
+            data,
+        );
+        let transfer = transaction(&owner, &[transfer]);
+
+        assert_eq!(
+            restricted(&[]).check(&transfer),
+            Err(PolicyViolation::UnrecognizedInstruction {
+                instruction: 0,
+                program_id: TOKEN_2022_PROGRAM_ID
+            })
+        );
+        assert_eq!(SigningPolicy::default().check(&transfer), Ok(()));
+    }
+
+    #[test]
+    fn checks_transfer_destinations() {
+        let (owner, attacker, trusted) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let policy = restricted(&[trusted]);
+
+        let to_attacker = system_instruction::transfer(&owner, &attacker, 1);
+        assert_eq!(
+            policy.check(&transaction(&owner, &[to_attacker])),
+            Err(PolicyViolation::DestinationNotAllowed {
+                destination: attacker
+            })
+        );
+        let to_trusted = system_instruction::transfer(&owner, &trusted, 1);
+        assert_eq!(policy.check(&transaction(&owner, &[to_trusted])), Ok(()));
+    }
+}
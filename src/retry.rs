@@ -0,0 +1,261 @@
+use std::time::{Duration, SystemTime};
+
+use rand::Rng;
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    Response, StatusCode,
+};
+
+/// Status codes treated as transient by `RetryPolicy::default()` and
+/// `FuryError::is_retryable`.
+pub const DEFAULT_RETRYABLE_STATUSES: [StatusCode; 6] = [
+    StatusCode::REQUEST_TIMEOUT,
+    StatusCode::TOO_MANY_REQUESTS,
+    StatusCode::INTERNAL_SERVER_ERROR,
+    StatusCode::BAD_GATEWAY,
+    StatusCode::SERVICE_UNAVAILABLE,
+    StatusCode::GATEWAY_TIMEOUT,
+];
+
+/// Controls how `FurySDK` retries failed HTTP requests.
+///
+/// Only idempotent endpoints are retried by default. Endpoints that submit
+/// transactions (e.g. `transactions/send`) are attempted once unless
+/// `retry_non_idempotent` is set.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled on every subsequent attempt.
+    pub base_delay: Duration,
+    /// Upper bound for a single delay, including `Retry-After` hints.
+    pub max_delay: Duration,
+    /// Randomize each delay between half and the full backoff value.
+    pub jitter: bool,
+    /// Response status codes that trigger a retry.
+    pub retryable_statuses: Vec<StatusCode>,
+    /// Retry when the request timed out.
+    pub retry_on_timeout: bool,
+    /// Retry when the connection could not be established.
+    pub retry_on_connect: bool,
+    /// Allow retrying requests that are not safe to re-submit.
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            jitter: true,
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
+            retry_on_timeout: true,
+            retry_on_connect: true,
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that performs a single attempt and never retries.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Number of attempts allowed for a request with the given idempotency.
+    pub fn attempts_for(&self, idempotent: bool) -> u32 {
+        if idempotent || self.retry_non_idempotent {
+            self.max_attempts.max(1)
+        } else {
+            1
+        }
+    }
+
+    pub fn is_retryable_status(&self, status: StatusCode) -> bool {
+        self.retryable_statuses.contains(&status)
+    }
+
+    pub fn is_retryable_error(&self, error: &reqwest::Error) -> bool {
+        (self.retry_on_timeout && error.is_timeout())
+            || (self.retry_on_connect && error.is_connect())
+    }
+
+    /// Backoff delay to wait after the given (1-based) failed attempt.
+    pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self
+            .base_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+
+        if self.jitter && !delay.is_zero() {
+            let half = delay / 2;
+            half + rand::thread_rng().gen_range(Duration::ZERO..=half)
+        } else {
+            delay
+        }
+    }
+
+    /// Backoff delay for a retryable response, honouring `Retry-After` when
+    /// the server provides it in seconds or as an HTTP date.
+    pub(crate) fn delay_for_response(&self, attempt: u32, response: &Response) -> Duration {
+        match retry_after(response.headers()) {
+            Some(delay) => delay.min(self.max_delay),
+            None => self.delay_for_attempt(attempt),
+        }
+    }
+}
+
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    // A date in the past means no wait
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(retry_after: Option<&str>) -> Response {
+        let mut response = http::Response::builder().status(StatusCode::TOO_MANY_REQUESTS);
+        if let Some(retry_after) = retry_after {
+            response = response.header(RETRY_AFTER, retry_after);
+        }
+        response.body("").unwrap().into()
+    }
+
+    #[test]
+    fn delay_doubles_within_jitter_bounds() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            ..Default::default()
+        };
+
+        for (attempt, backoff) in [(1, 100), (2, 200), (3, 400), (4, 800)] {
+            let backoff = Duration::from_millis(backoff);
+            for _ in 0..50 {
+                let delay = policy.delay_for_attempt(attempt);
+                assert!(
+                    delay >= backoff / 2 && delay <= backoff,
+                    "attempt {}: {:?}",
+                    attempt,
+                    delay
+                );
+            }
+        }
+
+        let policy = RetryPolicy {
+            jitter: false,
+            ..policy
+        };
+        assert_eq!(policy.delay_for_attempt(3), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn delay_is_capped_at_max_delay() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(3),
+            jitter: false,
+            ..Default::default()
+        };
+
+        assert_eq!(policy.delay_for_attempt(3), Duration::from_secs(3));
+        // Overflowing backoff
+        assert_eq!(policy.delay_for_attempt(100), Duration::from_secs(3));
+
+        let policy = RetryPolicy {
+            jitter: true,
+            ..policy
+        };
+        for attempt in [3, 40, u32::MAX] {
+            assert!(policy.delay_for_attempt(attempt) <= Duration::from_secs(3));
+        }
+    }
+
+    #[test]
+    fn honours_retry_after() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(30),
+            jitter: false,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            policy.delay_for_response(1, &response(Some("7"))),
+            Duration::from_secs(7)
+        );
+        let in_ten_seconds = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(10));
+        let delay = policy.delay_for_response(1, &response(Some(&in_ten_seconds)));
+        assert!(
+            delay > Duration::from_secs(8) && delay <= Duration::from_secs(10),
+            "{:?}",
+            delay
+        );
+        let past = httpdate::fmt_http_date(SystemTime::now() - Duration::from_secs(60));
+        assert_eq!(
+            policy.delay_for_response(1, &response(Some(&past))),
+            Duration::ZERO
+        );
+
+        // Missing or invalid hints fall back to the backoff
+        for retry_after in [None, Some("soon")] {
+            assert_eq!(
+                policy.delay_for_response(2, &response(retry_after)),
+                Duration::from_millis(200)
+            );
+        }
+    }
+
+    #[test]
+    fn retry_after_is_capped_at_max_delay() {
+        let policy = RetryPolicy {
+            max_delay: Duration::from_secs(5),
+            ..Default::default()
+        };
+        let in_an_hour = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(3600));
+
+        for retry_after in ["3600", in_an_hour.as_str()] {
+            assert_eq!(
+                policy.delay_for_response(1, &response(Some(retry_after))),
+                Duration::from_secs(5)
+            );
+        }
+    }
+
+    #[test]
+    fn non_idempotent_requests_are_attempted_once() {
+        let policy = RetryPolicy {
+            max_attempts: 4,
+            ..Default::default()
+        };
+        assert_eq!(policy.attempts_for(true), 4);
+        assert_eq!(policy.attempts_for(false), 1);
+
+        let policy = RetryPolicy {
+            retry_non_idempotent: true,
+            ..policy
+        };
+        assert_eq!(policy.attempts_for(false), 4);
+
+        let policy = RetryPolicy {
+            max_attempts: 0,
+            ..Default::default()
+        };
+        assert_eq!(policy.attempts_for(true), 1);
+    }
+}
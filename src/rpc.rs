@@ -0,0 +1,257 @@
+//! Minimal JSON-RPC 2.0 client for Solana RPC nodes and the Jito block
+//! engine.
+
+use std::fmt::{self, Display};
+
+use reqwest::{Client, Url};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+
+use crate::sdk::{FuryError, HttpErrorResponse};
+
+/// Error object of a JSON-RPC response
+#[derive(Debug, Clone, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default)]
+    pub data: Option<Value>,
+}
+
+impl Display for JsonRpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonRpcResponse {
+    #[serde(default)]
+    result: Value,
+    error: Option<JsonRpcError>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct JsonRpcClient {
+    client: Client,
+    url: Url,
+}
+
+impl JsonRpcClient {
+    /// Query parameters are kept, since many RPC providers pass the API key
+    /// that way
+    pub(crate) fn new(client: Client, url: &str) -> Result<Self, FuryError> {
+        let url = Url::parse(url)
+            .map_err(|e| FuryError::ConfigError(format!("Invalid RPC URL {}: {}", url, e)))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(FuryError::ConfigError(format!(
+                "RPC URL must use http or https: {}",
+                url
+            )));
+        }
+        Ok(Self { client, url })
+    }
+
+    pub(crate) async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<T, FuryError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let response = self
+            .client
+            .post(self.url.clone())
+            .json(&request)
+            .send()
+            .await
+            .map_err(FuryError::RequestError)?;
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.text().await.map_err(FuryError::RequestError)?;
+
+        // Some servers answer JSON-RPC errors with a non-2xx status
+        match serde_json::from_str::<JsonRpcResponse>(&body) {
+            Ok(JsonRpcResponse {
+                error: Some(error), ..
+            }) => Err(FuryError::RpcError(error)),
+            Ok(JsonRpcResponse { result, .. }) if status.is_success() => {
+                serde_json::from_value(result).map_err(|e| {
+                    FuryError::Other(anyhow::anyhow!("Invalid {} response: {}", method, e))
+                })
+            }
+            Err(e) if status.is_success() => Err(FuryError::Other(anyhow::anyhow!(
+                "Invalid {} response: {}",
+                method,
+                e
+            ))),
+            _ => Err(FuryError::HttpError(Box::new(HttpErrorResponse {
+                status,
+                headers,
+                body,
+            }))),
+        }
+    }
+}
+
+/// Local JSON-RPC server for tests, answering each call with a handler
+#[cfg(test)]
+pub(crate) mod mock {
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    use serde_json::{json, Value};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+        task::JoinHandle,
+    };
+
+    type Handler = dyn Fn(&str, &Value) -> (u16, Value) + Send + Sync;
+
+    pub(crate) struct MockRpcServer {
+        pub(crate) url: String,
+        calls: Arc<Mutex<Vec<String>>>,
+        task: JoinHandle<()>,
+    }
+
+    impl MockRpcServer {
+        /// `handler` gets the method and params of every call and returns
+        /// the HTTP status and body
+        pub(crate) async fn start(
+            handler: impl Fn(&str, &Value) -> (u16, Value) + Send + Sync + 'static,
+        ) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}/", listener.local_addr().unwrap());
+            let calls = Arc::new(Mutex::new(Vec::new()));
+            let handler: Arc<Handler> = Arc::new(handler);
+            let task = tokio::spawn({
+                let calls = calls.clone();
+                async move {
+                    while let Ok((stream, _)) = listener.accept().await {
+                        let (handler, calls) = (handler.clone(), calls.clone());
+                        tokio::spawn(async move {
+                            let _ = serve(stream, handler.as_ref(), &calls).await;
+                        });
+                    }
+                }
+            });
+            Self { url, calls, task }
+        }
+
+        /// Methods called so far, in order
+        pub(crate) fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    impl Drop for MockRpcServer {
+        fn drop(&mut self) {
+            self.task.abort();
+        }
+    }
+
+    pub(crate) fn result(result: Value) -> (u16, Value) {
+        (200, json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+    }
+
+    pub(crate) fn error(code: i64, message: &str) -> (u16, Value) {
+        let error = json!({ "code": code, "message": message });
+        (200, json!({ "jsonrpc": "2.0", "id": 1, "error": error }))
+    }
+
+    /// Answers a single request and closes the connection
+    async fn serve(
+        mut stream: TcpStream,
+        handler: &Handler,
+        calls: &Mutex<Vec<String>>,
+    ) -> io::Result<()> {
+        let mut buffer = Vec::new();
+        let header_end = loop {
+            let mut chunk = [0; 4096];
+            let read = stream.read(&mut chunk).await?;
+            if read == 0 {
+                return Ok(());
+            }
+            buffer.extend_from_slice(&chunk[..read]);
+            if let Some(position) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+                break position + 4;
+            }
+        };
+        let headers = String::from_utf8_lossy(&buffer[..header_end]).to_ascii_lowercase();
+        let content_length: usize = headers
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .and_then(|length| length.trim().parse().ok())
+            .unwrap_or(0);
+        while buffer.len() < header_end + content_length {
+            let mut chunk = [0; 4096];
+            let read = stream.read(&mut chunk).await?;
+            if read == 0 {
+                break;
+            }
+            buffer.extend_from_slice(&chunk[..read]);
+        }
+
+        let request: Value = serde_json::from_slice(&buffer[header_end..]).unwrap_or_default();
+        let method = request["method"].as_str().unwrap_or_default();
+        calls.lock().unwrap().push(method.to_string());
+        let (status, body) = handler(method, &request["params"]);
+        let body = body.to_string();
+        let response = format!(
+            "HTTP/1.1 {} Mock\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{mock::*, *};
+
+    #[tokio::test]
+    async fn returns_result() {
+        let server = MockRpcServer::start(|method, params| {
+            assert_eq!(method, "getBlockHeight");
+            assert_eq!(params, &json!([]));
+            result(json!(42))
+        })
+        .await;
+        let rpc = JsonRpcClient::new(Client::new(), &server.url).unwrap();
+
+        let height: u64 = rpc.call("getBlockHeight", json!([])).await.unwrap();
+        assert_eq!(height, 42);
+    }
+
+    #[tokio::test]
+    async fn surfaces_rpc_and_http_errors() {
+        let server = MockRpcServer::start(|method, _| match method {
+            "getBlockHeight" => error(-32601, "Method not found"),
+            _ => (503, json!("unavailable")),
+        })
+        .await;
+        let rpc = JsonRpcClient::new(Client::new(), &server.url).unwrap();
+
+        match rpc.call::<u64>("getBlockHeight", json!([])).await {
+            Err(FuryError::RpcError(error)) => assert_eq!(error.code, -32601),
+            other => panic!("expected RpcError, got {:?}", other),
+        }
+        match rpc.call::<u64>("getSlot", json!([])).await {
+            Err(FuryError::HttpError(error)) => assert_eq!(error.status.as_u16(), 503),
+            other => panic!("expected HttpError, got {:?}", other),
+        }
+    }
+}
@@ -3,10 +3,31 @@ use std::{
     collections::HashMap,
     fmt::{self, Display},
     str::FromStr,
+    sync::Arc,
 };
 
-use reqwest::Client;
+use reqwest::{header::HeaderMap, Client, StatusCode, Url};
 use serde::{Deserialize, Serialize};
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Signature};
+
+use crate::{
+    amount::{serialize_token_ui_number, Lamports, TokenAmount},
+    builder::{default_health_url, normalize_base_url, FurySDKBuilder},
+    cassette::{Cassette, CassetteError},
+    retry::{RetryPolicy, DEFAULT_RETRYABLE_STATUSES},
+    rpc::JsonRpcError,
+    serde_utils::{
+        display_fromstr, display_fromstr_keys, display_fromstr_option, display_fromstr_vec,
+    },
+    transactions::{SendableTransaction, UnsignedTransactions},
+    utils::SignError,
+};
+#[cfg(feature = "metrics")]
+use crate::metrics;
+#[cfg(feature = "tracing")]
+use crate::trace::{self, AddressLogging};
+
+pub const DEFAULT_BASE_URL: &str = "https://solana.fury.bot/api/";
 
 impl FromStr for Protocol {
     type Err = anyhow::Error;
@@ -37,7 +58,7 @@ impl Display for Protocol {
     }
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum Protocol {
     Raydium,
@@ -52,7 +73,7 @@ pub enum Protocol {
 // Analytics PNL
 // --------------------------------------------
 
-#[derive(Serialize)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct AnalyticsPnlOptions {
     pub include_timestamp: bool,
@@ -61,8 +82,10 @@ pub struct AnalyticsPnlOptions {
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AnalyticsPnlRequest {
+    // Comma separated wallet addresses
     pub addresses: String,
-    pub token_address: String,
+    #[serde(with = "display_fromstr")]
+    pub token_address: Pubkey,
     pub options: AnalyticsPnlOptions,
 }
 
@@ -77,7 +100,8 @@ pub struct AnalyticsPnlData {
 pub struct AnalyticsPnlResponse {
     pub success: bool,
     // Where key is wallet address and value is details
-    pub data: HashMap<String, AnalyticsPnlData>,
+    #[serde(deserialize_with = "display_fromstr_keys::deserialize")]
+    pub data: HashMap<Pubkey, AnalyticsPnlData>,
 }
 
 // --------------------------------------------
@@ -179,18 +203,21 @@ pub struct AnalyticsPnlResponse {
 // --------------------------------------------
 // Token buy
 // --------------------------------------------
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct BuyTokenRequest {
-    pub wallet_addresses: Vec<String>,
-    pub token_address: String,
-    pub sol_amount: f64,
+    #[serde(with = "display_fromstr_vec")]
+    pub wallet_addresses: Vec<Pubkey>,
+    #[serde(with = "display_fromstr")]
+    pub token_address: Pubkey,
+    pub sol_amount: Lamports,
     pub protocol: Protocol,
-    pub affiliate_address: Option<String>,
+    #[serde(serialize_with = "display_fromstr_option::serialize")]
+    pub affiliate_address: Option<Pubkey>,
     pub affiliate_fee: Option<String>,
     pub jito_tip_lamports: Option<u64>,
     pub slippage_bps: Option<u64>,
-    pub amounts: Option<Vec<f64>>, // TODO: maybe remove
+    pub amounts: Option<Vec<Lamports>>, // TODO: maybe remove
     pub use_rpc: bool, // TODO: maybe remove
 }
 
@@ -198,20 +225,23 @@ pub struct BuyTokenRequest {
 #[serde(rename_all = "snake_case")]
 pub struct BuyTokenResponse {
     pub success: bool,
-    pub transactions: Vec<String>,
+    pub transactions: UnsignedTransactions,
 }
 
 // --------------------------------------------
 // Token sell
 // --------------------------------------------
-#[derive(Serialize)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct SellRequest {
-    pub wallet_addresses: Vec<String>,
-    pub token_address: String,
+    #[serde(with = "display_fromstr_vec")]
+    pub wallet_addresses: Vec<Pubkey>,
+    #[serde(with = "display_fromstr")]
+    pub token_address: Pubkey,
     pub percentage: u64,
     pub protocol: Protocol,
-    pub affiliate_address: Option<String>,
+    #[serde(serialize_with = "display_fromstr_option::serialize")]
+    pub affiliate_address: Option<Pubkey>,
     pub affiliate_fee: Option<String>,
     pub jito_tip_lamports: Option<u64>,
     pub slippage_bps: Option<u64>,
@@ -221,19 +251,22 @@ pub struct SellRequest {
 #[serde(rename_all = "snake_case")]
 pub struct SellResponse {
     pub success: bool,
-    pub transactions: Vec<String>,
+    pub transactions: UnsignedTransactions,
 }
 
 // --------------------------------------------
 // Token transfer
 // --------------------------------------------
-#[derive(Serialize)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenTransferRequest {
-    pub sender_public_key: String,
-    pub receiver: String,
-    pub token_address: String,
-    pub amount: String,
+    #[serde(with = "display_fromstr")]
+    pub sender_public_key: Pubkey,
+    #[serde(with = "display_fromstr")]
+    pub receiver: Pubkey,
+    #[serde(with = "display_fromstr")]
+    pub token_address: Pubkey,
+    pub amount: TokenAmount,
 }
 
 #[derive(Deserialize, Debug)]
@@ -247,7 +280,8 @@ pub struct TokenTransferResponse {
 #[serde(rename_all = "snake_case")]
 pub struct TokenTransferData {
     pub transaction: String,
-    pub blockhash: String,
+    #[serde(with = "display_fromstr")]
+    pub blockhash: Hash,
     pub last_valid_block_height: u64,
     pub transfer_type: String,
 }
@@ -255,29 +289,31 @@ pub struct TokenTransferData {
 // --------------------------------------------
 // Token creation
 // --------------------------------------------
-#[derive(Serialize)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TokensCreateRequest {
-    pub wallet_addresses: Vec<String>,
-    pub mint_pubkey: String,
+    #[serde(with = "display_fromstr_vec")]
+    pub wallet_addresses: Vec<Pubkey>,
+    #[serde(with = "display_fromstr")]
+    pub mint_pubkey: Pubkey,
     pub config: TokenCreationConfig,
-    pub amounts: Vec<f64>,
+    pub amounts: Vec<Lamports>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenCreationConfig {
     pub token_creation: TokenCreation,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenCreation {
     pub metadata: TokenCreationMetadata,
-    pub default_sol_amount: f64,
+    pub default_sol_amount: Lamports,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenCreationMetadata {
     pub name: String,
@@ -293,29 +329,35 @@ pub struct TokenCreationMetadata {
 #[serde(rename_all = "snake_case")]
 pub struct TokensCreateResponse {
     pub success: bool,
-    pub transactions: Vec<String>,
+    pub transactions: UnsignedTransactions,
 }
 
 // --------------------------------------------
 // Token burn
 // --------------------------------------------
-#[derive(Serialize)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenBurnRequest {
-    pub wallet_public_key: String,
-    pub token_address: String,
-    pub amount: f64,
+    #[serde(with = "display_fromstr")]
+    pub wallet_public_key: Pubkey,
+    #[serde(with = "display_fromstr")]
+    pub token_address: Pubkey,
+    #[serde(serialize_with = "serialize_token_ui_number")]
+    pub amount: TokenAmount,
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct TokenBurnData {
     pub transaction: String,
-    pub blockhash: String,
+    #[serde(with = "display_fromstr")]
+    pub blockhash: Hash,
     pub amount: f64,
     pub decimals: u64,
-    pub token_mint: String,
-    pub associated_token_address: String,
+    #[serde(with = "display_fromstr")]
+    pub token_mint: Pubkey,
+    #[serde(with = "display_fromstr")]
+    pub associated_token_address: Pubkey,
 }
 
 #[derive(Deserialize, Debug)]
@@ -328,23 +370,27 @@ pub struct TokenBurnResponse {
 // --------------------------------------------
 // Token cleaner
 // --------------------------------------------
-#[derive(Serialize)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenCleanerRequest {
-    pub seller_address: String,
-    pub buyer_address: String,
-    pub token_address: String,
+    #[serde(with = "display_fromstr")]
+    pub seller_address: Pubkey,
+    #[serde(with = "display_fromstr")]
+    pub buyer_address: Pubkey,
+    #[serde(with = "display_fromstr")]
+    pub token_address: Pubkey,
     pub sell_percentage: f64,
     pub buy_percentage: f64,
-    pub wallet_addresses: Vec<String>,
-    pub buy_amount: f64, 
+    #[serde(with = "display_fromstr_vec")]
+    pub wallet_addresses: Vec<Pubkey>,
+    pub buy_amount: Lamports,
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct TokenCleanerResponse {
     pub success: bool,
-    pub transactions: Vec<String>,
+    pub transactions: UnsignedTransactions,
 }
 
 // --------------------------------------------
@@ -373,7 +419,8 @@ pub struct JitoTransactionSendResponse {
 
 #[derive(Deserialize, Debug)]
 pub struct RpcTxResult {
-    pub rpc: Vec<String>,
+    #[serde(with = "display_fromstr_vec")]
+    pub rpc: Vec<Signature>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -383,6 +430,69 @@ pub struct RpcTransactionSendResponse {
     pub result: RpcTxResult,
 }
 
+/// How `send_transactions` submits transactions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Delivery {
+    /// As a bundle through the Jito block engine
+    #[default]
+    Jito,
+    /// One by one through RPC nodes
+    Rpc,
+}
+
+/// Response of `send_transactions`, shaped by the delivery used
+#[derive(Debug)]
+pub enum SendOutcome {
+    Jito(JitoTransactionSendResponse),
+    Rpc(RpcTransactionSendResponse),
+}
+
+impl SendOutcome {
+    pub fn success(&self) -> bool {
+        match self {
+            SendOutcome::Jito(response) => response.success,
+            SendOutcome::Rpc(response) => response.success,
+        }
+    }
+
+    /// Turns a response reporting `success: false` into an `ApiError`
+    pub fn into_result(self) -> Result<SendOutcome, FuryError> {
+        if self.success() {
+            return Ok(self);
+        }
+        Err(FuryError::ApiError(ErrorResponse {
+            success: false,
+            error: Some("Transactions were not accepted".to_string()),
+            details: None,
+            status: None,
+        }))
+    }
+
+    pub fn delivery(&self) -> Delivery {
+        match self {
+            SendOutcome::Jito(_) => Delivery::Jito,
+            SendOutcome::Rpc(_) => Delivery::Rpc,
+        }
+    }
+
+    /// Result returned by the Jito block engine, `None` for RPC delivery.
+    /// This is the bundle id to pass to `BundleTracker`.
+    pub fn jito_result(&self) -> Option<&str> {
+        match self {
+            SendOutcome::Jito(response) => Some(&response.result.jito),
+            SendOutcome::Rpc(_) => None,
+        }
+    }
+
+    /// Signatures returned for RPC delivery, empty for Jito
+    pub fn signatures(&self) -> &[Signature] {
+        match self {
+            SendOutcome::Jito(_) => &[],
+            SendOutcome::Rpc(response) => &response.result.rpc,
+        }
+    }
+}
+
 // --------------------------------------------
 // Health check
 // --------------------------------------------
@@ -398,23 +508,26 @@ pub struct HealthCheckResponse {
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct GenerateMintResponse {
-    pub pubkey: String,
+    #[serde(with = "display_fromstr")]
+    pub pubkey: Pubkey,
 }
 
 // --------------------------------------------
 // Wallets distribute
 // --------------------------------------------
-#[derive(Serialize)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct WalletsDistributeRecipient {
-    pub address: String,
-    pub amount: f64,
+    #[serde(with = "display_fromstr")]
+    pub address: Pubkey,
+    pub amount: Lamports,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct WalletsDistributeRequest {
-    pub sender: String,
+    #[serde(with = "display_fromstr")]
+    pub sender: Pubkey,
     pub recipients: Vec<WalletsDistributeRecipient>,
 }
 
@@ -422,17 +535,19 @@ pub struct WalletsDistributeRequest {
 #[serde(rename_all = "snake_case")]
 pub struct WalletsDistributeResponse {
     pub success: bool,
-    pub transactions: Vec<String>,
+    pub transactions: UnsignedTransactions,
 }
 
 // --------------------------------------------
 // Wallets consolidate
 // --------------------------------------------
-#[derive(Serialize)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct WalletsConsolidateRequest {
-    pub source_addresses: Vec<String>,
-    pub receiver_address: String,
+    #[serde(with = "display_fromstr_vec")]
+    pub source_addresses: Vec<Pubkey>,
+    #[serde(with = "display_fromstr")]
+    pub receiver_address: Pubkey,
     pub percentage: f64,
 }
 
@@ -440,7 +555,7 @@ pub struct WalletsConsolidateRequest {
 #[serde(rename_all = "snake_case")]
 pub struct WalletsConsolidateResponse {
     pub success: bool,
-    pub transactions: Vec<String>,
+    pub transactions: UnsignedTransactions,
 }
 
 // --------------------------------------------
@@ -452,18 +567,72 @@ pub struct ErrorResponse {
     pub success: bool,
     pub error: Option<String>,
     pub details: Option<String>,
+    // Filled in from the HTTP response, not part of the body
+    #[serde(skip)]
+    pub status: Option<StatusCode>,
+}
+
+/// A non-2xx response whose body is not a valid `ErrorResponse`,
+/// e.g. an HTML page from a load balancer or an empty 429 body
+#[derive(Debug)]
+pub struct HttpErrorResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: String,
 }
 
 #[derive(Debug)]
 pub enum FuryError {
     ApiError(ErrorResponse),
+    HttpError(Box<HttpErrorResponse>),
     RequestError(reqwest::Error),
+    ConfigError(String),
+    /// A transaction could not be encoded for sending
+    TransactionError(SignError),
+    /// A Solana RPC node or the Jito block engine returned a JSON-RPC error
+    RpcError(JsonRpcError),
+    /// The cassette could not record or replay an exchange, e.g. no
+    /// recorded interaction matches the request
+    CassetteError(CassetteError),
     Other(anyhow::Error),
 }
 
+impl FuryError {
+    /// HTTP status of the failed request, if a response was received
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            FuryError::ApiError(error) => error.status,
+            FuryError::HttpError(error) => Some(error.status),
+            FuryError::RequestError(e) => e.status(),
+            FuryError::ConfigError(_)
+            | FuryError::TransactionError(_)
+            | FuryError::RpcError(_)
+            | FuryError::CassetteError(_)
+            | FuryError::Other(_) => None,
+        }
+    }
+
+    /// Whether the request failed due to rate limiting (HTTP 429)
+    pub fn is_rate_limited(&self) -> bool {
+        self.status() == Some(StatusCode::TOO_MANY_REQUESTS)
+    }
+
+    /// Whether the failure is transient and the request may succeed if retried
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            FuryError::RequestError(e) if e.is_timeout() || e.is_connect() => true,
+            _ => self
+                .status()
+                .is_some_and(|status| DEFAULT_RETRYABLE_STATUSES.contains(&status)),
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 struct RequestOptions {
-    base_url: Option<String>,
+    base_url: Option<Url>,
+    // Set for endpoints that must not be re-submitted on retry
+    non_idempotent: bool,
 }
 
 impl std::fmt::Display for FuryError {
@@ -485,7 +654,21 @@ impl std::fmt::Display for FuryError {
                     )
                 }
             }
+            FuryError::HttpError(error) => {
+                let body = error.body.trim();
+                if body.is_empty() {
+                    write!(f, "HTTP error: {}", error.status)
+                } else {
+                    // Keep HTML error pages from flooding logs
+                    let snippet: String = body.chars().take(200).collect();
+                    write!(f, "HTTP error: {}: {}", error.status, snippet)
+                }
+            }
             FuryError::RequestError(e) => write!(f, "Request error: {}", e),
+            FuryError::ConfigError(e) => write!(f, "Configuration error: {}", e),
+            FuryError::TransactionError(e) => write!(f, "Transaction error: {}", e),
+            FuryError::RpcError(e) => write!(f, "RPC error: {}", e),
+            FuryError::CassetteError(e) => write!(f, "{}", e),
             FuryError::Other(e) => write!(f, "Error: {}", e),
         }
     }
@@ -495,62 +678,269 @@ impl std::error::Error for FuryError {}
 
 pub struct FurySDK {
     pub client: Client,
-    pub base_url: String,
+    // API root that endpoints are joined onto, always ends with a slash.
+    // Private so the slash cannot be lost, set through the builder or
+    // `new_with_base_url`.
+    pub(crate) base_url: Url,
+    // Root of the `health` endpoint, also ends with a slash
+    pub(crate) health_url: Url,
+    pub retry_policy: RetryPolicy,
+    // Records or replays every HTTP exchange when set
+    pub cassette: Option<Arc<Cassette>>,
+    #[cfg(feature = "tracing")]
+    pub address_logging: AddressLogging,
 }
 
 impl FurySDK {
     pub fn new(client: Client) -> Self {
+        let base_url = Url::parse(DEFAULT_BASE_URL).expect("default base URL is valid");
         Self {
             client,
-            base_url: "https://solana.fury.bot/api/".to_string(),
+            health_url: default_health_url(&base_url),
+            base_url,
+            retry_policy: RetryPolicy::none(),
+            cassette: None,
+            #[cfg(feature = "tracing")]
+            address_logging: AddressLogging::default(),
         }
     }
 
-    pub fn new_with_base_url(client: Client, base_url: &str) -> Self {
-        Self {
+    /// Creates an SDK for a custom API root. A missing trailing slash is added;
+    /// anything other than an absolute http(s) URL is rejected.
+    pub fn new_with_base_url(client: Client, base_url: &str) -> Result<Self, FuryError> {
+        let base_url = normalize_base_url(base_url)?;
+        Ok(Self {
             client,
-            base_url: base_url.to_string(),
-        }
+            health_url: default_health_url(&base_url),
+            base_url,
+            retry_policy: RetryPolicy::none(),
+            cassette: None,
+            #[cfg(feature = "tracing")]
+            address_logging: AddressLogging::default(),
+        })
     }
 
+    /// API root that endpoints are joined onto, ending with a slash
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// Root of the `health` endpoint, ending with a slash
+    pub fn health_url(&self) -> &Url {
+        &self.health_url
+    }
+
+    /// Starts building a `FurySDK` with authentication, timeouts and other
+    /// client settings
+    pub fn builder() -> FurySDKBuilder {
+        FurySDKBuilder::new()
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Records requests to, or replays them from, `cassette`. Pass an
+    /// `Arc<Cassette>` to keep a handle, e.g. to check `remaining` after a
+    /// replay.
+    pub fn with_cassette(mut self, cassette: impl Into<Arc<Cassette>>) -> Self {
+        self.cassette = Some(cassette.into());
+        self
+    }
+
+    /// How wallet addresses appear in tracing spans
+    #[cfg(feature = "tracing")]
+    pub fn with_address_logging(mut self, address_logging: AddressLogging) -> Self {
+        self.address_logging = address_logging;
+        self
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(endpoint = "health")))]
     pub async fn health_check(&self) -> Result<HealthCheckResponse, FuryError> {
-        let base_url = self.base_url.clone().replace("api/", "");
         self.send_get_request(
             "health",
             None,
             RequestOptions {
-                base_url: Some(base_url),
+                base_url: Some(self.health_url.clone()),
+                ..Default::default()
             },
         )
         .await
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                endpoint = "tokens/buy",
+                token = %data.token_address,
+                wallets = data.wallet_addresses.len(),
+                addresses = self.address_logging.format(&data.wallet_addresses),
+                transactions = tracing::field::Empty,
+            )
+        )
+    )]
     pub async fn buy_token(&self, data: &BuyTokenRequest) -> Result<BuyTokenResponse, FuryError> {
-        self.send_post_request("tokens/buy", data, RequestOptions::default())
-            .await
+        let response: BuyTokenResponse = self
+            .send_post_request("tokens/buy", data, RequestOptions::default())
+            .await?;
+        #[cfg(feature = "tracing")]
+        trace::record_transactions(response.transactions.len());
+        Ok(response)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                endpoint = "tokens/sell",
+                token = %data.token_address,
+                wallets = data.wallet_addresses.len(),
+                addresses = self.address_logging.format(&data.wallet_addresses),
+                transactions = tracing::field::Empty,
+            )
+        )
+    )]
     pub async fn sell_token(&self, data: &SellRequest) -> Result<SellResponse, FuryError> {
-        self.send_post_request("tokens/sell", data, RequestOptions::default())
-            .await
+        let response: SellResponse = self
+            .send_post_request("tokens/sell", data, RequestOptions::default())
+            .await?;
+        #[cfg(feature = "tracing")]
+        trace::record_transactions(response.transactions.len());
+        Ok(response)
     }
 
+    /// Submits signed transactions, either base58 encoded or as
+    /// `VersionedTransaction`s, setting `use_rpc` from `delivery`
+    ///
+    /// Never retried unless the retry policy allows non-idempotent requests,
+    /// since a resubmitted bundle may land twice.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                endpoint = "transactions/send",
+                transactions = transactions.len(),
+                delivery = ?delivery,
+            )
+        )
+    )]
+    pub async fn send_transactions<T: SendableTransaction>(
+        &self,
+        transactions: &[T],
+        delivery: Delivery,
+    ) -> Result<SendOutcome, FuryError> {
+        let transactions = transactions
+            .iter()
+            .enumerate()
+            .map(|(index, transaction)| transaction.encode(index))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(FuryError::TransactionError)?;
+        let data = TransactionSendRequest {
+            transactions,
+            use_rpc: delivery == Delivery::Rpc,
+        };
+        let options = RequestOptions {
+            non_idempotent: true,
+            ..Default::default()
+        };
+
+        let outcome = match delivery {
+            Delivery::Jito => self
+                .send_post_request("transactions/send", &data, options)
+                .await
+                .map(SendOutcome::Jito),
+            Delivery::Rpc => self
+                .send_post_request("transactions/send", &data, options)
+                .await
+                .map(SendOutcome::Rpc),
+        };
+        #[cfg(feature = "metrics")]
+        metrics::record_send(delivery, outcome.as_ref().is_ok_and(SendOutcome::success));
+        outcome
+    }
+
+    #[deprecated(note = "use `send_transactions` with `Delivery::Jito`")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                endpoint = "transactions/send",
+                transactions = data.transactions.len(),
+            )
+        )
+    )]
     pub async fn jito_transaction_send(
         &self,
         data: &TransactionSendRequest,
     ) -> Result<JitoTransactionSendResponse, FuryError> {
-        self.send_post_request("transactions/send", data, RequestOptions::default())
-            .await
+        let response: Result<JitoTransactionSendResponse, FuryError> = self
+            .send_post_request(
+                "transactions/send",
+                data,
+                RequestOptions {
+                    non_idempotent: true,
+                    ..Default::default()
+                },
+            )
+            .await;
+        #[cfg(feature = "metrics")]
+        metrics::record_send(
+            Delivery::Jito,
+            response.as_ref().is_ok_and(|response| response.success),
+        );
+        response
     }
 
+    #[deprecated(note = "use `send_transactions` with `Delivery::Rpc`")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                endpoint = "transactions/send",
+                transactions = data.transactions.len(),
+            )
+        )
+    )]
     pub async fn rpc_transaction_send(
         &self,
         data: &TransactionSendRequest,
     ) -> Result<RpcTransactionSendResponse, FuryError> {
-        self.send_post_request("transactions/send", data, RequestOptions::default())
-            .await
+        let response: Result<RpcTransactionSendResponse, FuryError> = self
+            .send_post_request(
+                "transactions/send",
+                data,
+                RequestOptions {
+                    non_idempotent: true,
+                    ..Default::default()
+                },
+            )
+            .await;
+        #[cfg(feature = "metrics")]
+        metrics::record_send(
+            Delivery::Rpc,
+            response.as_ref().is_ok_and(|response| response.success),
+        );
+        response
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                endpoint = "tokens/transfer",
+                token = %data.token_address,
+                addresses = self.address_logging.format(&[data.sender_public_key, data.receiver]),
+            )
+        )
+    )]
     pub async fn token_transfer(
         &self,
         data: &TokenTransferRequest,
@@ -559,14 +949,42 @@ impl FurySDK {
             .await
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                endpoint = "tokens/create",
+                token = %data.mint_pubkey,
+                wallets = data.wallet_addresses.len(),
+                addresses = self.address_logging.format(&data.wallet_addresses),
+                transactions = tracing::field::Empty,
+            )
+        )
+    )]
     pub async fn tokens_create(
         &self,
         data: &TokensCreateRequest,
     ) -> Result<TokensCreateResponse, FuryError> {
-        self.send_post_request("tokens/create", data, RequestOptions::default())
-            .await
+        let response: TokensCreateResponse = self
+            .send_post_request("tokens/create", data, RequestOptions::default())
+            .await?;
+        #[cfg(feature = "tracing")]
+        trace::record_transactions(response.transactions.len());
+        Ok(response)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                endpoint = "tokens/burn",
+                token = %data.token_address,
+                addresses = self.address_logging.format(&[data.wallet_public_key]),
+            )
+        )
+    )]
     pub async fn token_burn(
         &self,
         data: &TokenBurnRequest,
@@ -575,22 +993,55 @@ impl FurySDK {
             .await
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                endpoint = "tokens/cleaner",
+                token = %data.token_address,
+                wallets = data.wallet_addresses.len(),
+                addresses = self.address_logging.format(&data.wallet_addresses),
+                transactions = tracing::field::Empty,
+            )
+        )
+    )]
     pub async fn token_cleaner(
         &self,
         data: &TokenCleanerRequest,
     ) -> Result<TokenCleanerResponse, FuryError> {
-        self.send_post_request("tokens/cleaner", data, RequestOptions::default())
-            .await
+        let response: TokenCleanerResponse = self
+            .send_post_request("tokens/cleaner", data, RequestOptions::default())
+            .await?;
+        #[cfg(feature = "tracing")]
+        trace::record_transactions(response.transactions.len());
+        Ok(response)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                endpoint = "analytics/pnl",
+                token = %token_address,
+                wallets = addresses.len(),
+                addresses = self.address_logging.format(addresses),
+            )
+        )
+    )]
     pub async fn analytics_pnl(
         &self,
-        addresses: Vec<String>,
-        token_address: String,
+        addresses: &[Pubkey],
+        token_address: Pubkey,
         options: AnalyticsPnlOptions,
     ) -> Result<AnalyticsPnlResponse, FuryError> {
         let data = AnalyticsPnlRequest {
-            addresses: addresses.join(","),
+            addresses: addresses
+                .iter()
+                .map(|address| address.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
             token_address,
             options,
         };
@@ -655,25 +1106,63 @@ impl FurySDK {
     //     .await
     // }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(endpoint = "utilities/generate-mint"))
+    )]
     pub async fn generate_mint(&self) -> Result<GenerateMintResponse, FuryError> {
         self.send_get_request("utilities/generate-mint", None, RequestOptions::default())
             .await
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                endpoint = "wallets/distribute",
+                wallets = data.recipients.len(),
+                addresses = self
+                    .address_logging
+                    .format(&data.recipients.iter().map(|r| r.address).collect::<Vec<_>>()),
+                transactions = tracing::field::Empty,
+            )
+        )
+    )]
     pub async fn wallets_distribute(
         &self,
         data: &WalletsDistributeRequest,
     ) -> Result<WalletsDistributeResponse, FuryError> {
-        self.send_post_request("wallets/distribute", data, RequestOptions::default())
-            .await
+        let response: WalletsDistributeResponse = self
+            .send_post_request("wallets/distribute", data, RequestOptions::default())
+            .await?;
+        #[cfg(feature = "tracing")]
+        trace::record_transactions(response.transactions.len());
+        Ok(response)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                endpoint = "wallets/consolidate",
+                wallets = data.source_addresses.len(),
+                addresses = self.address_logging.format(&data.source_addresses),
+                transactions = tracing::field::Empty,
+            )
+        )
+    )]
     pub async fn wallets_consolidate(
         &self,
         data: &WalletsConsolidateRequest,
     ) -> Result<WalletsConsolidateResponse, FuryError> {
-        self.send_post_request("wallets/consolidate", data, RequestOptions::default())
-            .await
+        let response: WalletsConsolidateResponse = self
+            .send_post_request("wallets/consolidate", data, RequestOptions::default())
+            .await?;
+        #[cfg(feature = "tracing")]
+        trace::record_transactions(response.transactions.len());
+        Ok(response)
     }
 
     /// Generic method to send a POST request and handle the response
@@ -697,19 +1186,21 @@ impl FurySDK {
         T: for<'de> Deserialize<'de>,
         D: Serialize,
     {
-        let base_url = options.base_url.unwrap_or(self.base_url.clone());
-        let response = match self
-            .client
-            .post(&format!("{}{}", base_url, endpoint))
-            .json(data)
-            .send()
-            .await
-        {
-            Ok(resp) => resp,
-            Err(e) => return Err(FuryError::RequestError(e)),
+        let url = self.endpoint_url(endpoint, &options)?;
+        let request = async {
+            let response = self
+                .send_with_retry(
+                    endpoint,
+                    || self.client.post(url.clone()).json(data),
+                    &options,
+                )
+                .await?;
+            self.process_response(response).await
         };
+        #[cfg(feature = "tracing")]
+        let request = tracing::Instrument::instrument(request, trace::request_span("POST", &url));
 
-        self.process_response(response).await
+        request.await
     }
 
     /// Generic method to send a POST request and handle the response
@@ -733,34 +1224,139 @@ impl FurySDK {
         T: for<'de> Deserialize<'de>,
     {
         let params = params.unwrap_or_default();
-        let base_url = options.base_url.unwrap_or(self.base_url.clone());
-        let response = match self
-            .client
-            .get(&format!("{}{}", base_url, endpoint))
-            .query(&params)
-            .send()
-            .await
-        {
-            Ok(resp) => resp,
-            Err(e) => return Err(FuryError::RequestError(e)),
+        let url = self.endpoint_url(endpoint, &options)?;
+        let request = async {
+            let response = self
+                .send_with_retry(
+                    endpoint,
+                    || self.client.get(url.clone()).query(&params),
+                    &options,
+                )
+                .await?;
+            self.process_response(response).await
         };
+        #[cfg(feature = "tracing")]
+        let request = tracing::Instrument::instrument(request, trace::request_span("GET", &url));
+
+        request.await
+    }
 
-        self.process_response(response).await
+    fn endpoint_url(&self, endpoint: &str, options: &RequestOptions) -> Result<Url, FuryError> {
+        let base_url = options.base_url.as_ref().unwrap_or(&self.base_url);
+        base_url
+            .join(endpoint)
+            .map_err(|e| FuryError::Other(anyhow::anyhow!("Invalid endpoint {}: {}", endpoint, e)))
+    }
+
+    /// Sends the request built by `build`, retrying according to the
+    /// SDK's `RetryPolicy`
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The API endpoint, used to label metrics
+    /// * `build` - Builds a fresh request for every attempt
+    /// * `options` - Request options, used to determine idempotency
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(reqwest::Response)` - The last response received
+    /// * `Err(FuryError)` - The request could not be sent
+    async fn send_with_retry<F>(
+        &self,
+        #[cfg_attr(not(feature = "metrics"), allow(unused_variables))] endpoint: &str,
+        build: F,
+        options: &RequestOptions,
+    ) -> Result<reqwest::Response, FuryError>
+    where
+        F: Fn() -> reqwest::RequestBuilder,
+    {
+        let policy = &self.retry_policy;
+        let max_attempts = policy.attempts_for(!options.non_idempotent);
+        let mut attempt = 1;
+        #[cfg(feature = "tracing")]
+        let start = std::time::Instant::now();
+
+        loop {
+            #[cfg(feature = "metrics")]
+            let attempt_start = std::time::Instant::now();
+            let result = match &self.cassette {
+                Some(cassette) => match cassette.send(build()).await {
+                    Ok(response) => Ok(response),
+                    Err(CassetteError::Request(e)) => Err(e),
+                    Err(e) => {
+                        #[cfg(feature = "metrics")]
+                        metrics::record_request(endpoint, None, attempt_start.elapsed());
+                        #[cfg(feature = "tracing")]
+                        trace::record_failure(&e, attempt, start.elapsed());
+                        return Err(FuryError::CassetteError(e));
+                    }
+                },
+                None => build().send().await,
+            };
+            #[cfg(feature = "metrics")]
+            metrics::record_request(
+                endpoint,
+                result.as_ref().ok().map(|response| response.status()),
+                attempt_start.elapsed(),
+            );
+            let can_retry = attempt < max_attempts;
+            let delay = match &result {
+                Ok(resp) if can_retry && policy.is_retryable_status(resp.status()) => {
+                    policy.delay_for_response(attempt, resp)
+                }
+                Err(e) if can_retry && policy.is_retryable_error(e) => {
+                    policy.delay_for_attempt(attempt)
+                }
+                _ => {
+                    #[cfg(feature = "tracing")]
+                    trace::record_response(&result, attempt, start.elapsed());
+                    return result.map_err(FuryError::RequestError);
+                }
+            };
+
+            #[cfg(feature = "tracing")]
+            trace::record_retry(&result, attempt, delay);
+            #[cfg(feature = "metrics")]
+            metrics::record_retry(endpoint);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
     }
 
     async fn process_response<T>(&self, response: reqwest::Response) -> Result<T, FuryError>
     where
         T: for<'de> Deserialize<'de>,
     {
-        // println!("text: {:#?}", response);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(?response, "Raw FURY API response");
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = match response.bytes().await {
+            Ok(body) => body,
+            Err(e) => return Err(FuryError::RequestError(e)),
+        };
+        #[cfg(feature = "tracing")]
+        trace::record_response_bytes(body.len());
+
+        if status.is_success() {
+            return serde_json::from_slice(&body).map_err(|e| {
+                FuryError::Other(anyhow::anyhow!("Invalid FURY API response: {}", e))
+            });
+        }
+
+        let body = String::from_utf8_lossy(&body).into_owned();
 
-        if response.status().is_success() {
-            match response.json().await {
-                Ok(body) => Ok(body),
-                Err(e) => Err(FuryError::RequestError(e)),
+        match serde_json::from_str::<ErrorResponse>(&body) {
+            Ok(mut error) => {
+                error.status = Some(status);
+                Err(FuryError::ApiError(error))
             }
-        } else {
-            Err(FuryError::ApiError(response.json().await.unwrap()))
+            Err(_) => Err(FuryError::HttpError(Box::new(HttpErrorResponse {
+                status,
+                headers,
+                body,
+            }))),
         }
     }
 }
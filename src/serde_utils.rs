@@ -0,0 +1,89 @@
+//! Serde helpers for types that travel as strings on the wire, such as
+//! base58 `Pubkey`, `Signature` and `Hash` values.
+
+use std::{collections::HashMap, fmt::Display, hash::Hash, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+pub mod display_fromstr {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Display,
+        S: Serializer,
+    {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        T::from_str(&s).map_err(|e| de::Error::custom(format!("{}: {}", e, s)))
+    }
+}
+
+pub mod display_fromstr_option {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Display,
+        S: Serializer,
+    {
+        match value {
+            Some(value) => serializer.collect_str(value),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+pub mod display_fromstr_vec {
+    use super::*;
+
+    pub fn serialize<T, S>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Display,
+        S: Serializer,
+    {
+        serializer.collect_seq(values.iter().map(|value| value.to_string()))
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|s| T::from_str(s).map_err(|e| de::Error::custom(format!("{}: {}", e, s))))
+            .collect()
+    }
+}
+
+/// Maps keyed by a string-encoded type, e.g. `HashMap<Pubkey, V>`
+pub mod display_fromstr_keys {
+    use super::*;
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where
+        K: FromStr + Eq + Hash,
+        K::Err: Display,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        HashMap::<String, V>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(s, value)| {
+                K::from_str(&s)
+                    .map(|key| (key, value))
+                    .map_err(|e| de::Error::custom(format!("{}: {}", e, s)))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,177 @@
+//! Signers that need I/O to produce a signature, such as remote signing
+//! services or hardware wallets.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use solana_sdk::{pubkey::Pubkey, signature::Signature, signer::Signer};
+
+/// A signer whose `sign` may perform network I/O.
+///
+/// Method names differ from `Signer` so both traits can be in scope at once.
+///
+/// Every `solana_sdk::signer::Signer` (e.g. `Keypair`) is also an
+/// `AsyncSigner`, so in-memory and remote keys can be mixed in one signer
+/// set.
+#[async_trait]
+pub trait AsyncSigner: Send + Sync {
+    /// Public key of the account this signer signs for
+    fn signer_pubkey(&self) -> Pubkey;
+
+    /// Signs a serialized transaction message
+    async fn sign(&self, message: &[u8]) -> Result<Signature>;
+}
+
+#[async_trait]
+impl<T> AsyncSigner for T
+where
+    T: Signer + Send + Sync,
+{
+    fn signer_pubkey(&self) -> Pubkey {
+        self.pubkey()
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<Signature> {
+        Ok(self.try_sign_message(message)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use solana_sdk::{signature::Keypair, transaction::VersionedTransaction};
+
+    use super::*;
+    use crate::{
+        test_support::{base58, unsigned_transfer},
+        utils::{sign_each_transaction_async, sign_transactions_async, SignError},
+    };
+
+    enum Behavior {
+        Sign,
+        Fail,
+        WrongKey,
+    }
+
+    /// In-process stand-in for a remote signing service
+    struct MockSigner {
+        keypair: Keypair,
+        behavior: Behavior,
+        calls: AtomicUsize,
+    }
+
+    impl MockSigner {
+        fn new(behavior: Behavior) -> Self {
+            MockSigner {
+                keypair: Keypair::new(),
+                behavior,
+                calls: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl AsyncSigner for MockSigner {
+        fn signer_pubkey(&self) -> Pubkey {
+            self.keypair.pubkey()
+        }
+
+        async fn sign(&self, message: &[u8]) -> Result<Signature> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            // Give up the thread like a network round trip would
+            tokio::task::yield_now().await;
+            match self.behavior {
+                Behavior::Sign => Ok(self.keypair.sign_message(message)),
+                Behavior::Fail => Err(anyhow::anyhow!("signing service unavailable")),
+                Behavior::WrongKey => Ok(Keypair::new().sign_message(message)),
+            }
+        }
+    }
+
+    /// Base58 unsigned transfer from `from`, paid by `payer`
+    fn unsigned_transaction(payer: &Pubkey, from: &Pubkey) -> String {
+        base58(&unsigned_transfer(payer, from, &Pubkey::new_unique(), 1))
+    }
+
+    fn decode(transaction: &str) -> VersionedTransaction {
+        bincode::deserialize(&bs58::decode(transaction).into_vec().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn signs_with_async_and_local_signers() {
+        let remote = MockSigner::new(Behavior::Sign);
+        let local = Keypair::new();
+        let transactions = vec![
+            unsigned_transaction(&remote.signer_pubkey(), &local.pubkey()),
+            unsigned_transaction(&remote.signer_pubkey(), &remote.signer_pubkey()),
+        ];
+
+        let signed = sign_transactions_async(&transactions, &[&remote, &local])
+            .await
+            .unwrap();
+
+        assert_eq!(signed.len(), 2);
+        for transaction in &signed {
+            let transaction = decode(transaction);
+            assert!(transaction.verify_with_results().iter().all(|ok| *ok));
+        }
+        assert_eq!(remote.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn reports_missing_signer() {
+        let remote = MockSigner::new(Behavior::Sign);
+        let absent = Keypair::new();
+        let transactions = vec![
+            unsigned_transaction(&remote.signer_pubkey(), &remote.signer_pubkey()),
+            unsigned_transaction(&remote.signer_pubkey(), &absent.pubkey()),
+        ];
+
+        let results = sign_each_transaction_async(&transactions, &[&remote]).await;
+
+        assert!(results[0].is_ok());
+        match &results[1] {
+            Err(SignError::MissingSigner { index, pubkey }) => {
+                assert_eq!(*index, 1);
+                assert_eq!(*pubkey, absent.pubkey());
+            }
+            other => panic!("expected MissingSigner, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_signer_error() {
+        let remote = MockSigner::new(Behavior::Fail);
+        let transactions = vec![unsigned_transaction(
+            &remote.signer_pubkey(),
+            &remote.signer_pubkey(),
+        )];
+
+        match sign_transactions_async(&transactions, &[&remote]).await {
+            Err(SignError::Signer {
+                index,
+                pubkey,
+                source,
+            }) => {
+                assert_eq!(index, 0);
+                assert_eq!(pubkey, remote.signer_pubkey());
+                assert_eq!(source.to_string(), "signing service unavailable");
+            }
+            other => panic!("expected Signer error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_signature_from_wrong_key() {
+        let remote = MockSigner::new(Behavior::WrongKey);
+        let transactions = vec![unsigned_transaction(
+            &remote.signer_pubkey(),
+            &remote.signer_pubkey(),
+        )];
+
+        assert!(matches!(
+            sign_transactions_async(&transactions, &[&remote]).await,
+            Err(SignError::InvalidSignature { index: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,529 @@
+//! Offline execution of FURY API transactions against an in-process SVM.
+//!
+//! The VM starts with the builtin and SPL programs only. Everything else a
+//! transaction touches (wallets, mints, bonding curves, pool accounts and
+//! the programs owning them) has to be loaded from account snapshots, e.g.
+//! a fixture file written from `solana account --output json` dumps:
+//!
+//! ```json
+//! {
+//!   "accounts": [
+//!     {
+//!       "pubkey": "FuRytmqsoo4mKQAhNXoB64JD4SsiVqxYkUKC6i1VaBot",
+//!       "account": {
+//!         "lamports": 1000000000,
+//!         "data": ["", "base64"],
+//!         "owner": "11111111111111111111111111111111",
+//!         "executable": false,
+//!         "rentEpoch": 0
+//!       }
+//!     }
+//!   ],
+//!   "programs": [
+//!     { "programId": "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P", "path": "pump.so" }
+//!   ]
+//! }
+//! ```
+//!
+//! Program paths are relative to the fixture file.
+
+use std::{
+    fmt::{self, Display},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use litesvm::{
+    types::{FailedTransactionMetadata, SimulatedTransactionInfo},
+    LiteSVM,
+};
+use serde::Deserialize;
+use solana_sdk::{
+    account::{Account, ReadableAccount},
+    pubkey::Pubkey,
+    transaction::{TransactionError, VersionedTransaction},
+};
+
+use crate::{
+    inspect::{TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID},
+    serde_utils::display_fromstr,
+    transactions::UnsignedTransactions,
+};
+
+#[derive(Debug)]
+pub enum SimulationError {
+    /// The fixture or a program file could not be read
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The fixture is not valid JSON or an account is malformed
+    Fixture(String),
+    /// The VM rejected an account or program
+    Load { pubkey: Pubkey, message: String },
+}
+
+impl Display for SimulationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulationError::Io { path, source } => {
+                write!(f, "Failed to read {}: {}", path.display(), source)
+            }
+            SimulationError::Fixture(message) => write!(f, "Invalid fixture: {}", message),
+            SimulationError::Load { pubkey, message } => {
+                write!(f, "Failed to load {}: {}", pubkey, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SimulationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SimulationError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Accounts and programs to load into a `Simulator`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationFixture {
+    #[serde(default)]
+    pub accounts: Vec<AccountSnapshot>,
+    #[serde(default)]
+    pub programs: Vec<ProgramSnapshot>,
+}
+
+/// An account in the JSON layout used by `solana account --output json`
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccountSnapshot {
+    #[serde(with = "display_fromstr")]
+    pub pubkey: Pubkey,
+    pub account: AccountData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountData {
+    pub lamports: u64,
+    /// Encoded data and its encoding, `"base64"` or `"base58"`
+    pub data: (String, String),
+    #[serde(with = "display_fromstr")]
+    pub owner: Pubkey,
+    #[serde(default)]
+    pub executable: bool,
+    #[serde(default)]
+    pub rent_epoch: u64,
+}
+
+/// An SBF program loaded from a `.so` file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgramSnapshot {
+    #[serde(with = "display_fromstr")]
+    pub program_id: Pubkey,
+    pub path: PathBuf,
+}
+
+impl SimulationFixture {
+    pub fn from_json(json: &str) -> Result<Self, SimulationError> {
+        serde_json::from_str(json).map_err(|e| SimulationError::Fixture(e.to_string()))
+    }
+
+    /// Reads a fixture file, resolving program paths relative to it
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SimulationError> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path).map_err(|source| SimulationError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let mut fixture = Self::from_json(&json)?;
+        if let Some(dir) = path.parent() {
+            for program in &mut fixture.programs {
+                program.path = dir.join(&program.path);
+            }
+        }
+        Ok(fixture)
+    }
+}
+
+impl AccountData {
+    pub fn to_account(&self) -> Result<Account, SimulationError> {
+        let (encoded, encoding) = &self.data;
+        let data = match encoding.as_str() {
+            "base64" => BASE64_STANDARD
+                .decode(encoded)
+                .map_err(|e| SimulationError::Fixture(e.to_string()))?,
+            "base58" => bs58::decode(encoded)
+                .into_vec()
+                .map_err(|e| SimulationError::Fixture(e.to_string()))?,
+            other => {
+                return Err(SimulationError::Fixture(format!(
+                    "unsupported encoding {}",
+                    other
+                )))
+            }
+        };
+        Ok(Account {
+            lamports: self.lamports,
+            data,
+            owner: self.owner,
+            executable: self.executable,
+            rent_epoch: self.rent_epoch,
+        })
+    }
+}
+
+/// Balance of an account before and after a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceChange {
+    pub pubkey: Pubkey,
+    pub pre_lamports: u64,
+    pub post_lamports: u64,
+    /// Raw amount held, if the account is an SPL token account
+    pub pre_token_amount: Option<u64>,
+    pub post_token_amount: Option<u64>,
+}
+
+impl BalanceChange {
+    pub fn lamports_delta(&self) -> i128 {
+        self.post_lamports as i128 - self.pre_lamports as i128
+    }
+
+    pub fn token_delta(&self) -> Option<i128> {
+        match (self.pre_token_amount, self.post_token_amount) {
+            (None, None) => None,
+            (pre, post) => Some(post.unwrap_or(0) as i128 - pre.unwrap_or(0) as i128),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationResult {
+    /// Why the transaction failed, `None` if it succeeded
+    pub error: Option<TransactionError>,
+    pub logs: Vec<String>,
+    pub compute_units_consumed: u64,
+    /// Every account the transaction loaded. Empty if it failed.
+    pub balance_changes: Vec<BalanceChange>,
+}
+
+impl SimulationResult {
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    pub fn balance_change(&self, pubkey: &Pubkey) -> Option<&BalanceChange> {
+        self.balance_changes
+            .iter()
+            .find(|change| change.pubkey == *pubkey)
+    }
+}
+
+/// In-process SVM preloaded with account snapshots.
+///
+/// Signature and blockhash checks are disabled, so transactions can be
+/// simulated before they are signed and long after their blockhash expired.
+#[derive(Clone)]
+pub struct Simulator {
+    svm: LiteSVM,
+}
+
+impl Default for Simulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Simulator {
+    pub fn new() -> Self {
+        Self {
+            svm: LiteSVM::new()
+                .with_sigverify(false)
+                .with_blockhash_check(false),
+        }
+    }
+
+    pub fn from_fixture(fixture: &SimulationFixture) -> Result<Self, SimulationError> {
+        let mut simulator = Self::new();
+        simulator.load_fixture(fixture)?;
+        Ok(simulator)
+    }
+
+    pub fn from_fixture_file(path: impl AsRef<Path>) -> Result<Self, SimulationError> {
+        Self::from_fixture(&SimulationFixture::from_file(path)?)
+    }
+
+    pub fn load_fixture(&mut self, fixture: &SimulationFixture) -> Result<(), SimulationError> {
+        for snapshot in &fixture.accounts {
+            self.set_account(snapshot.pubkey, snapshot.account.to_account()?)?;
+        }
+        for program in &fixture.programs {
+            let bytes = fs::read(&program.path).map_err(|source| SimulationError::Io {
+                path: program.path.clone(),
+                source,
+            })?;
+            self.add_program(program.program_id, &bytes)?;
+        }
+        Ok(())
+    }
+
+    pub fn set_account(&mut self, pubkey: Pubkey, account: Account) -> Result<(), SimulationError> {
+        self.svm
+            .set_account(pubkey, account)
+            .map_err(|e| SimulationError::Load {
+                pubkey,
+                message: e.to_string(),
+            })
+    }
+
+    pub fn add_program(&mut self, program_id: Pubkey, bytes: &[u8]) -> Result<(), SimulationError> {
+        self.svm
+            .add_program(program_id, bytes)
+            .map_err(|e| SimulationError::Load {
+                pubkey: program_id,
+                message: e.to_string(),
+            })
+    }
+
+    pub fn get_account(&self, pubkey: &Pubkey) -> Option<Account> {
+        self.svm.get_account(pubkey)
+    }
+
+    /// Runs a transaction without changing any account
+    pub fn simulate(&self, transaction: &VersionedTransaction) -> SimulationResult {
+        self.run(transaction).0
+    }
+
+    /// Runs a transaction and keeps its effects if it succeeds, so later
+    /// transactions see the updated accounts
+    pub fn execute(&mut self, transaction: &VersionedTransaction) -> SimulationResult {
+        let (result, post_accounts) = self.run(transaction);
+        for (pubkey, account) in post_accounts {
+            // Accounts already passed the VM's own checks
+            let _ = self.svm.set_account(pubkey, account);
+        }
+        result
+    }
+
+    /// Executes the transactions in order, as a bundle or a buy followed by
+    /// a sell would land. A failed transaction leaves the accounts unchanged
+    /// and does not stop the ones after it.
+    pub fn execute_all(&mut self, transactions: &UnsignedTransactions) -> Vec<SimulationResult> {
+        transactions
+            .iter()
+            .map(|transaction| self.execute(transaction))
+            .collect()
+    }
+
+    fn run(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> (SimulationResult, Vec<(Pubkey, Account)>) {
+        match self.svm.simulate_transaction(transaction.clone()) {
+            Ok(SimulatedTransactionInfo {
+                meta,
+                post_accounts,
+            }) => {
+                let post_accounts: Vec<(Pubkey, Account)> = post_accounts
+                    .into_iter()
+                    .map(|(pubkey, account)| (pubkey, account.into()))
+                    .collect();
+                let balance_changes = post_accounts
+                    .iter()
+                    .map(|(pubkey, post)| {
+                        let pre = self.svm.get_account(pubkey);
+                        BalanceChange {
+                            pubkey: *pubkey,
+                            pre_lamports: pre.as_ref().map_or(0, |pre| pre.lamports),
+                            post_lamports: post.lamports,
+                            pre_token_amount: pre.as_ref().and_then(token_amount),
+                            post_token_amount: token_amount(post),
+                        }
+                    })
+                    .collect();
+                let result = SimulationResult {
+                    error: None,
+                    logs: meta.logs,
+                    compute_units_consumed: meta.compute_units_consumed,
+                    balance_changes,
+                };
+                (result, post_accounts)
+            }
+            Err(FailedTransactionMetadata { err, meta }) => {
+                let result = SimulationResult {
+                    error: Some(err),
+                    logs: meta.logs,
+                    compute_units_consumed: meta.compute_units_consumed,
+                    balance_changes: Vec::new(),
+                };
+                (result, Vec::new())
+            }
+        }
+    }
+}
+
+/// Amount field of an SPL token account, which both token programs store at
+/// bytes 64..72
+fn token_amount(account: &Account) -> Option<u64> {
+    if account.owner != TOKEN_PROGRAM_ID && account.owner != TOKEN_2022_PROGRAM_ID {
+        return None;
+    }
+    // Token accounts are 165 bytes. Token-2022 appends extensions after an
+    // account type byte, which is also present on mints with extensions.
+    const ACCOUNT_LEN: usize = 165;
+    const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+    let data = account.data();
+    if data.len() < ACCOUNT_LEN
+        || (data.len() > ACCOUNT_LEN && data[ACCOUNT_LEN] != ACCOUNT_TYPE_ACCOUNT)
+    {
+        return None;
+    }
+    Some(u64::from_le_bytes(data[64..72].try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{signature::Keypair, signer::Signer};
+    use solana_system_interface::program as system_program;
+
+    use super::*;
+    use crate::{
+        api::Endpoint,
+        executor::{TradeErrorKind, TradeExecutor, TradeRequest},
+        fake::FakeFuryApi,
+        sdk::BuyTokenResponse,
+        signer::AsyncSigner,
+        test_support::{buy_request, jito_sent, unsigned_transfer},
+    };
+
+    const SOL: u64 = 1_000_000_000;
+
+    /// Fixture holding one SOL in `wallet`, in the `solana account` layout
+    fn fixture(wallet: &Pubkey) -> SimulationFixture {
+        SimulationFixture::from_json(&format!(
+            r#"{{
+                "accounts": [{{
+                    "pubkey": "{}",
+                    "account": {{
+                        "lamports": {},
+                        "data": ["", "base64"],
+                        "owner": "{}",
+                        "executable": false,
+                        "rentEpoch": 0
+                    }}
+                }}]
+            }}"#,
+            wallet,
+            SOL,
+            system_program::ID
+        ))
+        .unwrap()
+    }
+
+    /// Unsigned transfers from `wallet`, as FURY API returns them
+    fn transfers(wallet: &Pubkey, to: &Pubkey, lamports: &[u64]) -> UnsignedTransactions {
+        UnsignedTransactions::new(
+            lamports
+                .iter()
+                .map(|lamports| unsigned_transfer(wallet, wallet, to, *lamports))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn simulates_unsigned_and_signed_transactions() {
+        let (wallet, to) = (Keypair::new(), Pubkey::new_unique());
+        let simulator = Simulator::from_fixture(&fixture(&wallet.pubkey())).unwrap();
+        let unsigned = transfers(&wallet.pubkey(), &to, &[SOL / 10]);
+
+        let result = &unsigned.simulate(&simulator)[0];
+        assert!(result.is_success(), "{:?}", result.error);
+        assert_eq!(
+            result.balance_change(&to).unwrap().lamports_delta(),
+            100_000_000
+        );
+        assert!(
+            result
+                .balance_change(&wallet.pubkey())
+                .unwrap()
+                .lamports_delta()
+                < -100_000_000
+        );
+        // Simulating leaves the accounts untouched
+        assert_eq!(
+            simulator.get_account(&wallet.pubkey()).unwrap().lamports,
+            SOL
+        );
+
+        let signed = unsigned.sign(&[&wallet]).unwrap();
+        assert!(simulator.simulate(&signed[0]).is_success());
+    }
+
+    #[test]
+    fn executes_transactions_in_order() {
+        let (wallet, to) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut simulator = Simulator::from_fixture(&fixture(&wallet)).unwrap();
+
+        let results = simulator.execute_all(&transfers(&wallet, &to, &[SOL / 2, SOL / 2]));
+
+        assert!(results[0].is_success());
+        assert!(!results[1].is_success());
+        assert!(results[1].balance_changes.is_empty());
+        assert_eq!(simulator.get_account(&to).unwrap().lamports, SOL / 2);
+    }
+
+    #[tokio::test]
+    async fn executor_signs_and_sends_after_simulation() {
+        let (wallet, to) = (Keypair::new(), Pubkey::new_unique());
+        let fake = FakeFuryApi::new();
+        fake.push_response(BuyTokenResponse {
+            success: true,
+            transactions: transfers(&wallet.pubkey(), &to, &[SOL / 10]),
+        });
+        fake.push_response(jito_sent(true));
+        let simulator = Simulator::from_fixture(&fixture(&wallet.pubkey())).unwrap();
+
+        let report = TradeExecutor::new(&fake)
+            .with_simulator(simulator)
+            .execute(
+                &TradeRequest::Buy(buy_request(&[wallet.pubkey()])),
+                &[&wallet as &dyn AsyncSigner],
+            )
+            .await
+            .unwrap();
+
+        assert!(report.simulation[0].is_success());
+        assert!(report.transactions[0].verify_with_results()[0]);
+        assert!(report.send.unwrap().success());
+    }
+
+    #[tokio::test]
+    async fn executor_stops_before_signing_when_simulation_fails() {
+        let (wallet, to) = (Keypair::new(), Pubkey::new_unique());
+        let fake = FakeFuryApi::new();
+        fake.push_response(BuyTokenResponse {
+            success: true,
+            transactions: transfers(&wallet.pubkey(), &to, &[SOL / 2, SOL]),
+        });
+        let simulator = Simulator::from_fixture(&fixture(&wallet.pubkey())).unwrap();
+
+        let error = TradeExecutor::new(&fake)
+            .with_simulator(simulator)
+            .execute(
+                &TradeRequest::Buy(buy_request(&[wallet.pubkey()])),
+                &[&wallet as &dyn AsyncSigner],
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error.kind,
+            TradeErrorKind::Simulation { index: 1, .. }
+        ));
+        assert!(fake.calls_to(Endpoint::SendTransactions).is_empty());
+    }
+}
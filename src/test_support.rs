@@ -0,0 +1,75 @@
+//! Fixtures shared by the unit tests, and by the mock server for its
+//! default responses
+
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    message::{Message, VersionedMessage},
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::VersionedTransaction,
+};
+use solana_system_interface::instruction as system_instruction;
+
+#[cfg(test)]
+use crate::{
+    sdk::{BuyTokenRequest, JitoTransactionSendResponse, JitoTxResult, Protocol, SendOutcome},
+    utils::serialize_transaction,
+};
+
+/// Unsigned legacy transaction paid by `payer`, with an empty signature for
+/// every required signer
+pub(crate) fn unsigned_transaction(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+) -> VersionedTransaction {
+    let message = Message::new_with_blockhash(instructions, Some(payer), &Hash::new_unique());
+    VersionedTransaction {
+        signatures: vec![Signature::default(); message.header.num_required_signatures as usize],
+        message: VersionedMessage::Legacy(message),
+    }
+}
+
+/// Unsigned transfer of `lamports` from `from` to `to`, paid by `payer`
+pub(crate) fn unsigned_transfer(
+    payer: &Pubkey,
+    from: &Pubkey,
+    to: &Pubkey,
+    lamports: u64,
+) -> VersionedTransaction {
+    unsigned_transaction(payer, &[system_instruction::transfer(from, to, lamports)])
+}
+
+/// Base58 wire format, as FURY API returns transactions
+#[cfg(test)]
+pub(crate) fn base58(transaction: &VersionedTransaction) -> String {
+    serialize_transaction(0, transaction).unwrap()
+}
+
+/// Buy of an unspecified token by `wallets`
+#[cfg(test)]
+pub(crate) fn buy_request(wallets: &[Pubkey]) -> BuyTokenRequest {
+    BuyTokenRequest {
+        wallet_addresses: wallets.to_vec(),
+        token_address: Pubkey::new_unique(),
+        sol_amount: 1_000u64.into(),
+        protocol: Protocol::Auto,
+        affiliate_address: None,
+        affiliate_fee: None,
+        jito_tip_lamports: None,
+        slippage_bps: None,
+        amounts: None,
+        use_rpc: false,
+    }
+}
+
+/// Response of a Jito bundle submission
+#[cfg(test)]
+pub(crate) fn jito_sent(success: bool) -> SendOutcome {
+    SendOutcome::Jito(JitoTransactionSendResponse {
+        success,
+        result: JitoTxResult {
+            jito: "bundle".to_string(),
+        },
+    })
+}
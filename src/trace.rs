@@ -0,0 +1,148 @@
+//! `tracing` instrumentation of `FurySDK`, enabled by the `tracing` feature.
+//!
+//! Every SDK method runs in a span named after it, with the endpoint and
+//! counts such as `wallets` and `transactions`. Each HTTP request is a child
+//! `http_request` span recording `http.status`, `attempts`, `latency_ms`
+//! and `response_bytes`. Retries and failures are also logged as events.
+//!
+//! ```no_run
+//! # use fury_sdk::{sdk::FurySDK, trace::AddressLogging};
+//! let fury = FurySDK::builder()
+//!     .address_logging(AddressLogging::Full)
+//!     .build()?;
+//! # Ok::<(), fury_sdk::sdk::FuryError>(())
+//! ```
+
+use std::{
+    fmt::{self, Display},
+    time::Duration,
+};
+
+use reqwest::{Response, Url};
+use solana_sdk::pubkey::Pubkey;
+use tracing::{
+    field::{display, DisplayValue, Empty},
+    Span,
+};
+
+/// How wallet addresses appear in the `addresses` field of spans. Counts
+/// are always recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AddressLogging {
+    /// Only the number of wallets
+    Hidden,
+    /// First and last four characters, e.g. `7xKX…gAsU`
+    #[default]
+    Abbreviated,
+    Full,
+}
+
+impl AddressLogging {
+    /// Value of the `addresses` field, `None` leaves it unset
+    pub(crate) fn format(self, addresses: &[Pubkey]) -> Option<DisplayValue<Addresses<'_>>> {
+        match self {
+            AddressLogging::Hidden => None,
+            _ => Some(display(Addresses {
+                addresses,
+                logging: self,
+            })),
+        }
+    }
+}
+
+pub(crate) struct Addresses<'a> {
+    addresses: &'a [Pubkey],
+    logging: AddressLogging,
+}
+
+impl Display for Addresses<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, address) in self.addresses.iter().enumerate() {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+            let address = address.to_string();
+            match self.logging {
+                AddressLogging::Abbreviated if address.len() > 8 => {
+                    write!(f, "{}…{}", &address[..4], &address[address.len() - 4..])?
+                }
+                _ => write!(f, "{}", address)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn request_span(method: &str, url: &Url) -> Span {
+    tracing::info_span!(
+        "http_request",
+        http.method = method,
+        url.path = url.path(),
+        http.status = Empty,
+        attempts = Empty,
+        latency_ms = Empty,
+        response_bytes = Empty,
+    )
+}
+
+/// Records the number of transactions returned on the current method span
+pub(crate) fn record_transactions(count: usize) {
+    Span::current().record("transactions", count);
+}
+
+pub(crate) fn record_retry(
+    result: &Result<Response, reqwest::Error>,
+    attempt: u32,
+    delay: Duration,
+) {
+    let delay_ms = delay.as_millis() as u64;
+    match result {
+        Ok(response) => tracing::warn!(
+            attempt,
+            delay_ms,
+            http.status = response.status().as_u16(),
+            "Retrying FURY API request"
+        ),
+        Err(e) => tracing::warn!(attempt, delay_ms, error = %e, "Retrying FURY API request"),
+    }
+}
+
+/// Records the final outcome on the current `http_request` span
+pub(crate) fn record_response(
+    result: &Result<Response, reqwest::Error>,
+    attempts: u32,
+    latency: Duration,
+) {
+    let span = Span::current();
+    let latency_ms = latency.as_millis() as u64;
+    span.record("attempts", attempts);
+    span.record("latency_ms", latency_ms);
+    match result {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            span.record("http.status", status);
+            tracing::debug!(
+                http.status = status,
+                latency_ms,
+                attempts,
+                "FURY API response"
+            );
+        }
+        Err(e) => record_failure(e, attempts, latency),
+    }
+}
+
+/// Records a request that got no response on the current `http_request`
+/// span
+pub(crate) fn record_failure(error: &dyn Display, attempts: u32, latency: Duration) {
+    let span = Span::current();
+    let latency_ms = latency.as_millis() as u64;
+    span.record("attempts", attempts);
+    span.record("latency_ms", latency_ms);
+    tracing::warn!(latency_ms, attempts, error = %error, "FURY API request failed");
+}
+
+/// Records the size of the response body on the current `http_request` span
+pub(crate) fn record_response_bytes(len: usize) {
+    Span::current().record("response_bytes", len);
+}
@@ -0,0 +1,362 @@
+//! Typed wrapper for the unsigned transactions returned by FURY API.
+
+use std::{ops::Index, slice};
+
+use serde::{de, Deserialize, Deserializer};
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signer::Signer, transaction::VersionedTransaction};
+
+#[cfg(feature = "simulation")]
+use crate::simulation::{SimulationResult, Simulator};
+use crate::{
+    inspect::{inspect_transaction, TransactionSummary},
+    policy::TransactionPolicy,
+    signer::AsyncSigner,
+    utils::{
+        check_policy, deserialize_transaction, missing_signatures, serialize_transaction,
+        sign_versioned_transaction, sign_versioned_transaction_async,
+        sign_versioned_transaction_partial, PartiallySignedTransaction, SignError,
+    },
+};
+
+/// Transactions returned by endpoints such as `tokens/buy` or
+/// `wallets/distribute`, decoded from base58 while the response is
+/// deserialized.
+///
+/// A malformed payload makes the whole response fail to deserialize, so
+/// every value held here is a structurally valid `VersionedTransaction`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UnsignedTransactions(Vec<VersionedTransaction>);
+
+impl UnsignedTransactions {
+    pub fn new(transactions: Vec<VersionedTransaction>) -> Self {
+        Self(transactions)
+    }
+
+    /// Decodes base58 encoded, bincode serialized transactions
+    pub fn from_base58<S: AsRef<str>>(serialized_txs: &[S]) -> Result<Self, SignError> {
+        serialized_txs
+            .iter()
+            .enumerate()
+            .map(|(index, serialized_tx)| deserialize_transaction(index, serialized_tx.as_ref()))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, VersionedTransaction> {
+        self.0.iter()
+    }
+
+    pub fn as_slice(&self) -> &[VersionedTransaction] {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> Vec<VersionedTransaction> {
+        self.0
+    }
+
+    /// Accounts that must sign the transaction at `index`
+    pub fn required_signers(&self, index: usize) -> Option<&[Pubkey]> {
+        self.0.get(index).map(required_signers)
+    }
+
+    /// Every account that must sign at least one of the transactions, in
+    /// order of first appearance
+    pub fn all_required_signers(&self) -> Vec<Pubkey> {
+        let mut signers: Vec<Pubkey> = Vec::new();
+        for signer in self.0.iter().flat_map(required_signers) {
+            if !signers.contains(signer) {
+                signers.push(*signer);
+            }
+        }
+        signers
+    }
+
+    /// Checks that `signers` covers every signature still missing, without
+    /// signing anything. Signatures already present, e.g. added by the API,
+    /// do not need a signer.
+    pub fn check_signers(&self, signers: &[Pubkey]) -> Result<(), SignError> {
+        for (index, transaction) in self.0.iter().enumerate() {
+            for (_, pubkey) in missing_signatures(index, transaction)? {
+                if !signers.contains(&pubkey) {
+                    return Err(SignError::MissingSigner { index, pubkey });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Recent blockhash of the transaction at `index`
+    pub fn recent_blockhash(&self, index: usize) -> Option<&Hash> {
+        self.0.get(index).map(|tx| tx.message.recent_blockhash())
+    }
+
+    /// Signs a copy of every transaction. Fails if any required signer is
+    /// missing from `signers`.
+    pub fn sign<S: Signer>(&self, signers: &[S]) -> Result<Vec<VersionedTransaction>, SignError> {
+        let mut transactions = self.0.clone();
+        for (index, transaction) in transactions.iter_mut().enumerate() {
+            sign_versioned_transaction(index, transaction, signers)?;
+        }
+        Ok(transactions)
+    }
+
+    /// Decodes every transaction into a structured summary
+    pub fn inspect(&self) -> Result<Vec<TransactionSummary>, SignError> {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(index, transaction)| {
+                inspect_transaction(transaction)
+                    .map_err(|source| SignError::Malformed { index, source })
+            })
+            .collect()
+    }
+
+    /// Simulates each transaction independently against `simulator`
+    #[cfg(feature = "simulation")]
+    pub fn simulate(&self, simulator: &Simulator) -> Vec<SimulationResult> {
+        self.0
+            .iter()
+            .map(|transaction| simulator.simulate(transaction))
+            .collect()
+    }
+
+    /// Runs `policy` on every transaction, returning the first violation
+    pub fn check_policy<P: TransactionPolicy + ?Sized>(&self, policy: &P) -> Result<(), SignError> {
+        self.0
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, transaction)| check_policy(index, transaction, policy))
+    }
+
+    /// Checks every transaction against `policy`, then signs them. Nothing
+    /// is signed if a single transaction is rejected.
+    pub fn sign_with_policy<S: Signer, P: TransactionPolicy + ?Sized>(
+        &self,
+        signers: &[S],
+        policy: &P,
+    ) -> Result<Vec<VersionedTransaction>, SignError> {
+        self.check_policy(policy)?;
+        self.sign(signers)
+    }
+
+    /// Signs a copy of every transaction with signers that may need network
+    /// I/O. Fails if any required signer is missing from `signers`.
+    pub async fn sign_async(
+        &self,
+        signers: &[&dyn AsyncSigner],
+    ) -> Result<Vec<VersionedTransaction>, SignError> {
+        let mut transactions = self.0.clone();
+        for (index, transaction) in transactions.iter_mut().enumerate() {
+            sign_versioned_transaction_async(index, transaction, signers).await?;
+        }
+        Ok(transactions)
+    }
+
+    /// Signs a copy of every transaction with whichever required signers are
+    /// present in `signers`, reporting the ones still missing
+    pub fn sign_partial<S: Signer>(
+        &self,
+        signers: &[S],
+    ) -> Result<Vec<PartiallySignedTransaction>, SignError> {
+        let mut res = Vec::new();
+        for (index, transaction) in self.0.iter().enumerate() {
+            let mut transaction = transaction.clone();
+            let missing_signers =
+                sign_versioned_transaction_partial(index, &mut transaction, signers)?;
+            res.push(PartiallySignedTransaction::new(
+                index,
+                transaction,
+                missing_signers,
+            ));
+        }
+        Ok(res)
+    }
+
+    /// Signs every transaction and encodes the result as base58, ready for
+    /// `TransactionSendRequest`
+    pub fn sign_to_base58<S: Signer>(&self, signers: &[S]) -> Result<Vec<String>, SignError> {
+        self.sign(signers)?
+            .iter()
+            .enumerate()
+            .map(|(index, transaction)| serialize_transaction(index, transaction))
+            .collect()
+    }
+
+    /// Encodes the transactions back to base58 without signing them
+    pub fn to_base58(&self) -> Result<Vec<String>, SignError> {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(index, transaction)| serialize_transaction(index, transaction))
+            .collect()
+    }
+}
+
+impl Index<usize> for UnsignedTransactions {
+    type Output = VersionedTransaction;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl IntoIterator for UnsignedTransactions {
+    type Item = VersionedTransaction;
+    type IntoIter = std::vec::IntoIter<VersionedTransaction>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a UnsignedTransactions {
+    type Item = &'a VersionedTransaction;
+    type IntoIter = slice::Iter<'a, VersionedTransaction>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'de> Deserialize<'de> for UnsignedTransactions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let serialized_txs = Vec::<String>::deserialize(deserializer)?;
+        UnsignedTransactions::from_base58(&serialized_txs).map_err(de::Error::custom)
+    }
+}
+
+/// A signed transaction accepted by `FurySDK::send_transactions`: a
+/// `VersionedTransaction` or a base58 encoded string
+pub trait SendableTransaction {
+    /// Base58 encoding of the transaction at `index` of its batch
+    fn encode(&self, index: usize) -> Result<String, SignError>;
+}
+
+impl SendableTransaction for VersionedTransaction {
+    fn encode(&self, index: usize) -> Result<String, SignError> {
+        serialize_transaction(index, self)
+    }
+}
+
+impl SendableTransaction for String {
+    fn encode(&self, _index: usize) -> Result<String, SignError> {
+        Ok(self.clone())
+    }
+}
+
+impl SendableTransaction for str {
+    fn encode(&self, _index: usize) -> Result<String, SignError> {
+        Ok(self.to_string())
+    }
+}
+
+impl<T: SendableTransaction + ?Sized> SendableTransaction for &T {
+    fn encode(&self, index: usize) -> Result<String, SignError> {
+        (**self).encode(index)
+    }
+}
+
+/// Accounts that must sign `transaction`
+pub fn required_signers(transaction: &VersionedTransaction) -> &[Pubkey] {
+    let keys = transaction.message.static_account_keys();
+    let required = transaction.message.header().num_required_signatures as usize;
+    &keys[..required.min(keys.len())]
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        sdk::BuyTokenResponse,
+        test_support::{base58, unsigned_transfer},
+    };
+
+    fn transfers(count: usize) -> Vec<VersionedTransaction> {
+        let payer = Pubkey::new_unique();
+        (0..count)
+            .map(|lamports| {
+                unsigned_transfer(&payer, &payer, &Pubkey::new_unique(), lamports as u64)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn decodes_base58_in_order() {
+        let transactions = transfers(3);
+        let encoded: Vec<String> = transactions.iter().map(base58).collect();
+
+        let decoded = UnsignedTransactions::from_base58(&encoded).unwrap();
+        assert_eq!(decoded, UnsignedTransactions::new(transactions.clone()));
+        assert_eq!(decoded.len(), 3);
+        assert!(!decoded.is_empty());
+        assert_eq!(decoded[1], transactions[1]);
+        assert!(decoded.iter().eq(transactions.iter()));
+        assert!((&decoded).into_iter().eq(transactions.iter()));
+        assert_eq!(decoded.to_base58().unwrap(), encoded);
+        assert_eq!(decoded.into_iter().collect::<Vec<_>>(), transactions);
+
+        assert!(UnsignedTransactions::from_base58::<&str>(&[])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn decodes_response_payloads() {
+        let transactions = transfers(2);
+        let body = json!({
+            "success": true,
+            "transactions": [base58(&transactions[0]), base58(&transactions[1])],
+        });
+
+        let response: BuyTokenResponse = serde_json::from_value(body).unwrap();
+        assert_eq!(response.transactions.into_inner(), transactions);
+    }
+
+    #[test]
+    fn reports_the_malformed_entry() {
+        let valid = base58(&transfers(1)[0]);
+
+        // `0`, `O`, `I` and `l` are not in the base58 alphabet, and base64
+        // payloads are not accepted
+        let not_base58 = UnsignedTransactions::from_base58(&[valid.as_str(), "0OIl+/=="]);
+        assert!(matches!(
+            not_base58,
+            Err(SignError::Decode { index: 1, .. })
+        ));
+
+        let not_a_transaction = bs58::encode([1, 2, 3]).into_string();
+        let truncated = UnsignedTransactions::from_base58(&[&valid, &not_a_transaction]);
+        assert!(matches!(
+            truncated,
+            Err(SignError::Deserialize { index: 1, .. })
+        ));
+
+        // A signature the message header does not require
+        let mut unsanitized = transfers(1).remove(0);
+        unsanitized.signatures.push(Default::default());
+        let unsanitized = UnsignedTransactions::from_base58(&[base58(&unsanitized), valid]);
+        assert!(matches!(
+            unsanitized,
+            Err(SignError::Malformed { index: 0, .. })
+        ));
+
+        let body = json!({ "success": true, "transactions": ["0OIl"] });
+        let error = serde_json::from_value::<BuyTokenResponse>(body).unwrap_err();
+        assert!(
+            error.to_string().contains("Transaction 0: invalid base58"),
+            "{}",
+            error
+        );
+    }
+}
@@ -1,54 +1,655 @@
-use anyhow::Result;
+use std::fmt::{self, Display};
+
 use bs58;
 use solana_sdk::{
-    signature::{Keypair, Signature},
-    signer::Signer,
+    pubkey::Pubkey, sanitize::SanitizeError, signature::Signature, signer::Signer,
     transaction::VersionedTransaction,
 };
-use std::sync::Arc;
-
-pub fn sign_transactions(
-    serialized_txs: &Vec<String>,
-    signers: &Vec<Arc<Keypair>>,
-) -> Result<Vec<String>> {
-    let mut res: Vec<String> = Vec::new();
-    for serialized_tx in serialized_txs.iter() {
-        let tx_buffer = bs58::decode(serialized_tx).into_vec().unwrap();
-        let mut transaction = match bincode::deserialize::<VersionedTransaction>(&tx_buffer) {
-            Ok(transaction) => transaction,
-            Err(e) => {
-                return Err(anyhow::anyhow!(
-                    "Failed to deserialize transaction: {:?}",
-                    e
-                ));
+
+use crate::{
+    policy::{PolicyViolation, TransactionPolicy},
+    signer::AsyncSigner,
+    transactions::required_signers,
+};
+
+/// Error raised while signing a batch of transactions. Every variant carries
+/// the position of the offending transaction in the batch.
+#[derive(Debug)]
+pub enum SignError {
+    /// The transaction is not valid base58
+    Decode {
+        index: usize,
+        source: bs58::decode::Error,
+    },
+    /// The bytes are not a bincode encoded `VersionedTransaction`
+    Deserialize {
+        index: usize,
+        source: bincode::Error,
+    },
+    /// The transaction is structurally invalid, e.g. its signature count
+    /// does not match its header
+    Malformed { index: usize, source: SanitizeError },
+    /// No signer was provided for a required account
+    MissingSigner { index: usize, pubkey: Pubkey },
+    /// Two partial results carry different signatures for the same account
+    ConflictingSignature { index: usize, pubkey: Pubkey },
+    /// A signature does not verify against the transaction message
+    InvalidSignature { index: usize, pubkey: Pubkey },
+    /// Partial results being merged contain different messages
+    MessageMismatch { index: usize },
+    /// Partial results being merged cover a different number of
+    /// transactions. `index` is the first transaction missing from one of
+    /// them.
+    LengthMismatch {
+        index: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// The signer failed to produce a signature
+    Signer {
+        index: usize,
+        pubkey: Pubkey,
+        source: anyhow::Error,
+    },
+    /// The signed transaction could not be encoded
+    Serialize {
+        index: usize,
+        source: bincode::Error,
+    },
+    /// The transaction was rejected by the signing policy
+    PolicyViolation {
+        index: usize,
+        violation: PolicyViolation,
+    },
+}
+
+impl SignError {
+    /// Position of the failed transaction in the batch
+    pub fn index(&self) -> usize {
+        match self {
+            SignError::Decode { index, .. }
+            | SignError::Deserialize { index, .. }
+            | SignError::Malformed { index, .. }
+            | SignError::MissingSigner { index, .. }
+            | SignError::ConflictingSignature { index, .. }
+            | SignError::InvalidSignature { index, .. }
+            | SignError::MessageMismatch { index }
+            | SignError::LengthMismatch { index, .. }
+            | SignError::Signer { index, .. }
+            | SignError::Serialize { index, .. }
+            | SignError::PolicyViolation { index, .. } => *index,
+        }
+    }
+}
+
+impl Display for SignError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignError::Decode { index, source } => {
+                write!(f, "Transaction {}: invalid base58: {}", index, source)
             }
-        };
+            SignError::Deserialize { index, source } => {
+                write!(
+                    f,
+                    "Transaction {}: failed to deserialize: {}",
+                    index, source
+                )
+            }
+            SignError::Malformed { index, source } => {
+                write!(f, "Transaction {}: malformed: {}", index, source)
+            }
+            SignError::MissingSigner { index, pubkey } => {
+                write!(f, "Transaction {}: signer not found for {}", index, pubkey)
+            }
+            SignError::ConflictingSignature { index, pubkey } => write!(
+                f,
+                "Transaction {}: conflicting signatures for {}",
+                index, pubkey
+            ),
+            SignError::InvalidSignature { index, pubkey } => {
+                write!(f, "Transaction {}: invalid signature for {}", index, pubkey)
+            }
+            SignError::MessageMismatch { index } => write!(
+                f,
+                "Transaction {}: message differs between partial results",
+                index
+            ),
+            SignError::LengthMismatch {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Transaction {}: partial results have different lengths: {} and {}",
+                index, expected, found
+            ),
+            SignError::Signer {
+                index,
+                pubkey,
+                source,
+            } => write!(
+                f,
+                "Transaction {}: signer {} failed: {}",
+                index, pubkey, source
+            ),
+            SignError::Serialize { index, source } => {
+                write!(f, "Transaction {}: failed to serialize: {}", index, source)
+            }
+            SignError::PolicyViolation { index, violation } => {
+                write!(
+                    f,
+                    "Transaction {}: rejected by policy: {}",
+                    index, violation
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SignError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SignError::Decode { source, .. } => Some(source),
+            SignError::Deserialize { source, .. } | SignError::Serialize { source, .. } => {
+                Some(source)
+            }
+            SignError::Malformed { source, .. } => Some(source),
+            SignError::Signer { source, .. } => Some(source.as_ref()),
+            SignError::PolicyViolation { violation, .. } => Some(violation),
+            _ => None,
+        }
+    }
+}
+
+/// Signs base58 encoded transactions with any set of `Signer`s, e.g.
+/// `&[Arc<Keypair>]`, `&[&dyn Signer]` or `&[Box<dyn Signer>]`
+///
+/// Fails on the first transaction that cannot be signed; use
+/// `sign_each_transaction` to keep the rest of the batch.
+pub fn sign_transactions<S: Signer>(
+    serialized_txs: &[String],
+    signers: &[S],
+) -> Result<Vec<String>, SignError> {
+    sign_each_transaction(serialized_txs, signers)
+        .into_iter()
+        .collect()
+}
+
+/// Signs every transaction independently, so one bad transaction does not
+/// discard the rest of the batch
+pub fn sign_each_transaction<S: Signer>(
+    serialized_txs: &[String],
+    signers: &[S],
+) -> Vec<Result<String, SignError>> {
+    serialized_txs
+        .iter()
+        .enumerate()
+        .map(|(index, serialized_tx)| {
+            let mut transaction = deserialize_transaction(index, serialized_tx)?;
+            sign_versioned_transaction(index, &mut transaction, signers)?;
+            serialize_transaction(index, &transaction)
+        })
+        .collect()
+}
+
+/// Checks every transaction against `policy` before signing any of them.
+/// Nothing is signed if a single transaction is rejected.
+pub fn sign_transactions_with_policy<S: Signer, P: TransactionPolicy + ?Sized>(
+    serialized_txs: &[String],
+    signers: &[S],
+    policy: &P,
+) -> Result<Vec<String>, SignError> {
+    let mut transactions = serialized_txs
+        .iter()
+        .enumerate()
+        .map(|(index, serialized_tx)| deserialize_transaction(index, serialized_tx))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (index, transaction) in transactions.iter().enumerate() {
+        check_policy(index, transaction, policy)?;
+    }
+    transactions
+        .iter_mut()
+        .enumerate()
+        .map(|(index, transaction)| {
+            sign_versioned_transaction(index, transaction, signers)?;
+            serialize_transaction(index, transaction)
+        })
+        .collect()
+}
+
+/// Signs base58 encoded transactions with signers that may need network I/O,
+/// such as a remote signing service
+pub async fn sign_transactions_async(
+    serialized_txs: &[String],
+    signers: &[&dyn AsyncSigner],
+) -> Result<Vec<String>, SignError> {
+    sign_each_transaction_async(serialized_txs, signers)
+        .await
+        .into_iter()
+        .collect()
+}
+
+/// Async counterpart of `sign_each_transaction`
+pub async fn sign_each_transaction_async(
+    serialized_txs: &[String],
+    signers: &[&dyn AsyncSigner],
+) -> Vec<Result<String, SignError>> {
+    let mut res = Vec::new();
+    for (index, serialized_tx) in serialized_txs.iter().enumerate() {
+        res.push(sign_serialized_transaction_async(index, serialized_tx, signers).await);
+    }
+    res
+}
+
+async fn sign_serialized_transaction_async(
+    index: usize,
+    serialized_tx: &str,
+    signers: &[&dyn AsyncSigner],
+) -> Result<String, SignError> {
+    let mut transaction = deserialize_transaction(index, serialized_tx)?;
+    sign_versioned_transaction_async(index, &mut transaction, signers).await?;
+    serialize_transaction(index, &transaction)
+}
+
+/// A transaction signed by the signers at hand, together with the signers
+/// that still have to sign it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartiallySignedTransaction {
+    /// Position of the transaction in its batch
+    pub index: usize,
+    pub transaction: VersionedTransaction,
+    /// Required signers whose signature is present
+    pub signed_by: Vec<Pubkey>,
+    /// Required signers whose signature is still missing
+    pub missing_signers: Vec<Pubkey>,
+}
+
+impl PartiallySignedTransaction {
+    pub(crate) fn new(
+        index: usize,
+        transaction: VersionedTransaction,
+        missing_signers: Vec<Pubkey>,
+    ) -> Self {
+        let signed_by = required_signers(&transaction)
+            .iter()
+            .filter(|signer| !missing_signers.contains(signer))
+            .copied()
+            .collect();
+        Self {
+            index,
+            transaction,
+            signed_by,
+            missing_signers,
+        }
+    }
+
+    pub fn is_fully_signed(&self) -> bool {
+        self.missing_signers.is_empty()
+    }
+
+    /// Encodes the transaction as base58, whether or not it is fully signed
+    pub fn to_base58(&self) -> Result<String, SignError> {
+        serialize_transaction(self.index, &self.transaction)
+    }
+}
+
+/// Signs base58 encoded transactions with whichever required signers are
+/// present in `signers`, reporting the ones that are missing instead of
+/// failing
+///
+/// Combine the results of every party with `finalize_partial_signatures`.
+pub fn sign_transactions_partial<S: Signer>(
+    serialized_txs: &[String],
+    signers: &[S],
+) -> Result<Vec<PartiallySignedTransaction>, SignError> {
+    let mut res = Vec::new();
+    for (index, serialized_tx) in serialized_txs.iter().enumerate() {
+        let mut transaction = deserialize_transaction(index, serialized_tx)?;
+        let missing_signers = sign_versioned_transaction_partial(index, &mut transaction, signers)?;
+        res.push(PartiallySignedTransaction::new(
+            index,
+            transaction,
+            missing_signers,
+        ));
+    }
+    Ok(res)
+}
+
+/// Async counterpart of `sign_transactions_partial`
+pub async fn sign_transactions_partial_async(
+    serialized_txs: &[String],
+    signers: &[&dyn AsyncSigner],
+) -> Result<Vec<PartiallySignedTransaction>, SignError> {
+    let mut res = Vec::new();
+    for (index, serialized_tx) in serialized_txs.iter().enumerate() {
+        let mut transaction = deserialize_transaction(index, serialized_tx)?;
+        let missing_signers =
+            sign_versioned_transaction_partial_async(index, &mut transaction, signers).await?;
+        res.push(PartiallySignedTransaction::new(
+            index,
+            transaction,
+            missing_signers,
+        ));
+    }
+    Ok(res)
+}
+
+/// Merges the signatures of several partial signing results of the same
+/// batch. Each entry in `parts` is one party's result, in batch order.
+///
+/// Fails if the parties signed different batches or messages, or a
+/// signature is invalid or conflicts with another party's.
+pub fn merge_partial_signatures(
+    parts: &[Vec<PartiallySignedTransaction>],
+) -> Result<Vec<PartiallySignedTransaction>, SignError> {
+    let Some((first, rest)) = parts.split_first() else {
+        return Ok(Vec::new());
+    };
+    if let Some(part) = rest.iter().find(|part| part.len() != first.len()) {
+        return Err(SignError::LengthMismatch {
+            index: first.len().min(part.len()),
+            expected: first.len(),
+            found: part.len(),
+        });
+    }
 
-        for (index, account_key) in transaction.message.static_account_keys().iter().enumerate() {
-            if transaction.message.is_signer(index) {
-                let sign_required = transaction.signatures[index] == Signature::default();
-                if sign_required {
-                    let signer = signers.iter().find(|kp| kp.pubkey().eq(&account_key));
-
-                    if let Some(signer) = signer {
-                        transaction.signatures[index] =
-                            signer.sign_message(&transaction.message.serialize());
-                    } else {
-                        return Err(anyhow::anyhow!(
-                            "Signer not found for account key: {:?}",
-                            account_key.to_string()
-                        ));
-                    }
+    let mut res = Vec::new();
+    for (tx_index, base) in first.iter().enumerate() {
+        let mut transaction = base.transaction.clone();
+        // Validates the signature count so indexing below stays in bounds
+        missing_signatures(tx_index, &transaction)?;
+        let message = transaction.message.serialize();
+        let signer_keys = required_signers(&transaction).to_vec();
+
+        for part in rest {
+            let other = &part[tx_index];
+            if other.transaction.message.serialize() != message {
+                return Err(SignError::MessageMismatch { index: tx_index });
+            }
+
+            for (index, signature) in other.transaction.signatures.iter().enumerate() {
+                let Some(current) = transaction.signatures.get(index) else {
+                    break;
+                };
+                if *signature == Signature::default() || current == signature {
+                    continue;
+                }
+
+                let pubkey = signer_keys[index];
+                if *current != Signature::default() {
+                    return Err(SignError::ConflictingSignature {
+                        index: tx_index,
+                        pubkey,
+                    });
+                }
+                if !signature.verify(pubkey.as_ref(), &message) {
+                    return Err(SignError::InvalidSignature {
+                        index: tx_index,
+                        pubkey,
+                    });
                 }
+                transaction.signatures[index] = *signature;
             }
         }
-        let tx_serialized = match bincode::serialize(&transaction) {
-            Ok(serialized) => serialized,
-            Err(e) => {
-                return Err(anyhow::anyhow!("Failed to serialize transaction: {:?}", e));
+
+        let missing_signers = missing_signatures(tx_index, &transaction)?
+            .into_iter()
+            .map(|(_, account_key)| account_key)
+            .collect();
+        res.push(PartiallySignedTransaction::new(
+            tx_index,
+            transaction,
+            missing_signers,
+        ));
+    }
+    Ok(res)
+}
+
+/// Merges partial signing results and encodes the fully signed transactions
+/// as base58. Fails if any transaction still lacks a signature.
+pub fn finalize_partial_signatures(
+    parts: &[Vec<PartiallySignedTransaction>],
+) -> Result<Vec<String>, SignError> {
+    merge_partial_signatures(parts)?
+        .iter()
+        .map(|partial| {
+            if let Some(pubkey) = partial.missing_signers.first() {
+                return Err(SignError::MissingSigner {
+                    index: partial.index,
+                    pubkey: *pubkey,
+                });
             }
+            let encoded = partial.to_base58()?;
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_signed_transaction();
+            Ok(encoded)
+        })
+        .collect()
+}
+
+/// Fills in every missing signature of `transaction` using `signers`
+///
+/// Fails if a required signer is not present in `signers`.
+pub(crate) fn sign_versioned_transaction<S: Signer>(
+    index: usize,
+    transaction: &mut VersionedTransaction,
+    signers: &[S],
+) -> Result<(), SignError> {
+    let missing_signers = sign_versioned_transaction_partial(index, transaction, signers)?;
+    ensure_fully_signed(index, &missing_signers)?;
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_signed_transaction();
+    Ok(())
+}
+
+/// Fills in the missing signatures of `transaction` that `signers` can
+/// provide and returns the signers that are still missing
+pub(crate) fn sign_versioned_transaction_partial<S: Signer>(
+    index: usize,
+    transaction: &mut VersionedTransaction,
+    signers: &[S],
+) -> Result<Vec<Pubkey>, SignError> {
+    let message = transaction.message.serialize();
+    let mut missing_signers = Vec::new();
+    for (position, account_key) in missing_signatures(index, transaction)? {
+        let signer = signers.iter().find(|kp| kp.pubkey().eq(&account_key));
+
+        if let Some(signer) = signer {
+            transaction.signatures[position] =
+                signer
+                    .try_sign_message(&message)
+                    .map_err(|e| SignError::Signer {
+                        index,
+                        pubkey: account_key,
+                        source: e.into(),
+                    })?;
+        } else {
+            missing_signers.push(account_key);
+        }
+    }
+    Ok(missing_signers)
+}
+
+/// Async counterpart of `sign_versioned_transaction`
+pub(crate) async fn sign_versioned_transaction_async(
+    index: usize,
+    transaction: &mut VersionedTransaction,
+    signers: &[&dyn AsyncSigner],
+) -> Result<(), SignError> {
+    let missing_signers =
+        sign_versioned_transaction_partial_async(index, transaction, signers).await?;
+    ensure_fully_signed(index, &missing_signers)?;
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_signed_transaction();
+    Ok(())
+}
+
+/// Async counterpart of `sign_versioned_transaction_partial`. Signatures
+/// returned by the signers are verified before they are attached.
+pub(crate) async fn sign_versioned_transaction_partial_async(
+    index: usize,
+    transaction: &mut VersionedTransaction,
+    signers: &[&dyn AsyncSigner],
+) -> Result<Vec<Pubkey>, SignError> {
+    let message = transaction.message.serialize();
+    let mut missing_signers = Vec::new();
+    for (position, account_key) in missing_signatures(index, transaction)? {
+        let signer = signers
+            .iter()
+            .find(|signer| signer.signer_pubkey() == account_key);
+
+        let Some(signer) = signer else {
+            missing_signers.push(account_key);
+            continue;
         };
-        res.push(bs58::encode(tx_serialized).into_string());
+
+        let signature = signer
+            .sign(&message)
+            .await
+            .map_err(|source| SignError::Signer {
+                index,
+                pubkey: account_key,
+                source,
+            })?;
+        if !signature.verify(account_key.as_ref(), &message) {
+            return Err(SignError::InvalidSignature {
+                index,
+                pubkey: account_key,
+            });
+        }
+        transaction.signatures[position] = signature;
+    }
+    Ok(missing_signers)
+}
+
+pub(crate) fn check_policy<P: TransactionPolicy + ?Sized>(
+    index: usize,
+    transaction: &VersionedTransaction,
+    policy: &P,
+) -> Result<(), SignError> {
+    policy
+        .check(transaction)
+        .map_err(|violation| SignError::PolicyViolation { index, violation })
+}
+
+fn ensure_fully_signed(index: usize, missing_signers: &[Pubkey]) -> Result<(), SignError> {
+    match missing_signers.first() {
+        Some(pubkey) => Err(SignError::MissingSigner {
+            index,
+            pubkey: *pubkey,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Signer positions of `transaction` that still hold the default signature
+pub(crate) fn missing_signatures(
+    index: usize,
+    transaction: &VersionedTransaction,
+) -> Result<Vec<(usize, Pubkey)>, SignError> {
+    transaction
+        .sanitize()
+        .map_err(|source| SignError::Malformed { index, source })?;
+
+    Ok(transaction
+        .message
+        .static_account_keys()
+        .iter()
+        .enumerate()
+        .filter(|(position, _)| transaction.message.is_signer(*position))
+        .filter(|(position, _)| transaction.signatures[*position] == Signature::default())
+        .map(|(position, account_key)| (position, *account_key))
+        .collect())
+}
+
+/// Decodes a base58 encoded, bincode serialized transaction
+pub(crate) fn deserialize_transaction(
+    index: usize,
+    serialized_tx: &str,
+) -> Result<VersionedTransaction, SignError> {
+    let tx_buffer = bs58::decode(serialized_tx)
+        .into_vec()
+        .map_err(|source| SignError::Decode { index, source })?;
+    let transaction = bincode::deserialize::<VersionedTransaction>(&tx_buffer)
+        .map_err(|source| SignError::Deserialize { index, source })?;
+    transaction
+        .sanitize()
+        .map_err(|source| SignError::Malformed { index, source })?;
+    Ok(transaction)
+}
+
+pub(crate) fn serialize_transaction(
+    index: usize,
+    transaction: &VersionedTransaction,
+) -> Result<String, SignError> {
+    match bincode::serialize(transaction) {
+        Ok(serialized) => Ok(bs58::encode(serialized).into_string()),
+        Err(source) => Err(SignError::Serialize { index, source }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signature::Keypair;
+
+    use super::*;
+    use crate::test_support::{base58, unsigned_transfer};
+
+    /// Base58 unsigned transfer from `from`, paid by `payer`
+    fn unsigned_transaction(payer: &Pubkey, from: &Pubkey) -> String {
+        base58(&unsigned_transfer(payer, from, &Pubkey::new_unique(), 1))
+    }
+
+    #[test]
+    fn finalizes_signatures_of_two_parties() {
+        let (payer, owner) = (Keypair::new(), Keypair::new());
+        let transactions = vec![unsigned_transaction(&payer.pubkey(), &owner.pubkey())];
+
+        let first = sign_transactions_partial(&transactions, &[&payer]).unwrap();
+        let second = sign_transactions_partial(&transactions, &[&owner]).unwrap();
+        assert_eq!(first[0].missing_signers, vec![owner.pubkey()]);
+
+        let signed = finalize_partial_signatures(&[first, second]).unwrap();
+        let transaction = deserialize_transaction(0, &signed[0]).unwrap();
+        assert!(transaction.verify_with_results().iter().all(|ok| *ok));
+    }
+
+    #[test]
+    fn rejects_parts_of_different_length() {
+        let (payer, owner) = (Keypair::new(), Keypair::new());
+        let transactions = vec![
+            unsigned_transaction(&payer.pubkey(), &owner.pubkey()),
+            unsigned_transaction(&payer.pubkey(), &owner.pubkey()),
+        ];
+
+        let first = sign_transactions_partial(&transactions, &[&payer]).unwrap();
+        let second = sign_transactions_partial(&transactions[..1], &[&owner]).unwrap();
+
+        assert!(matches!(
+            merge_partial_signatures(&[first, second]),
+            Err(SignError::LengthMismatch {
+                index: 1,
+                expected: 2,
+                found: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_conflicting_signatures() {
+        let (payer, owner) = (Keypair::new(), Keypair::new());
+        let transactions = vec![unsigned_transaction(&payer.pubkey(), &owner.pubkey())];
+
+        let first = sign_transactions_partial(&transactions, &[&payer]).unwrap();
+        let mut second = sign_transactions_partial(&transactions, &[&owner]).unwrap();
+        second[0].transaction.signatures[0] = Signature::from([7; 64]);
+
+        match merge_partial_signatures(&[first, second]) {
+            Err(SignError::ConflictingSignature { index, pubkey }) => {
+                assert_eq!(index, 0);
+                assert_eq!(pubkey, payer.pubkey());
+            }
+            other => panic!("expected ConflictingSignature, got {:?}", other),
+        }
     }
-    Ok(res)
 }
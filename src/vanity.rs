@@ -0,0 +1,353 @@
+//! Local grinding of vanity keypairs, e.g. mint addresses ending in `pump`,
+//! so a launch holds the mint's secret key instead of relying on
+//! `generate_mint`.
+//!
+//! ```no_run
+//! # use fury_sdk::vanity::{CancelToken, VanityGrinder};
+//! let grinder = VanityGrinder::new().with_suffix("pump");
+//! let keypair = grinder.grind(&CancelToken::new(), |progress| {
+//!     println!("{} keys, {:.0}/s, eta {:?}", progress.attempts, progress.rate, progress.eta);
+//! })?;
+//! # Ok::<(), fury_sdk::vanity::VanityError>(())
+//! ```
+//!
+//! Grinding blocks the calling thread; from async code run it with
+//! `tokio::task::spawn_blocking`. The keypair converts into a
+//! `launch::MintSource`.
+
+use std::{
+    fmt::{self, Display},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Length of the longest base58 encoded pubkey
+const MAX_ADDRESS_LEN: usize = 44;
+
+/// Keys a worker generates between checks for cancellation
+const BATCH_SIZE: u64 = 256;
+
+/// Longest the calling thread waits before noticing a cancellation
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VanityError {
+    /// The pattern contains a character that never appears in base58
+    /// addresses (`0`, `O`, `I`, `l`), is empty or is longer than an
+    /// address
+    InvalidPattern(String),
+    Cancelled,
+}
+
+impl Display for VanityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VanityError::InvalidPattern(message) => write!(f, "Invalid pattern: {}", message),
+            VanityError::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for VanityError {}
+
+/// Stops a running grind from another thread or task
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VanityProgress {
+    /// Keys generated so far, across all threads
+    pub attempts: u64,
+    pub elapsed: Duration,
+    /// Keys per second
+    pub rate: f64,
+    /// Average number of keys needed for a match
+    pub expected_attempts: f64,
+    /// Expected time until a match. Every key is an independent try, so this
+    /// does not shrink as attempts accumulate. `None` until the rate is
+    /// known.
+    pub eta: Option<Duration>,
+}
+
+impl VanityProgress {
+    /// Chance that a match would have been found by now
+    pub fn probability(&self) -> f64 {
+        1.0 - (1.0 - 1.0 / self.expected_attempts).powf(self.attempts as f64)
+    }
+}
+
+/// Generates keypairs on several threads until one's base58 address matches
+/// the prefix and suffix
+#[derive(Debug, Clone)]
+pub struct VanityGrinder {
+    pub prefix: String,
+    pub suffix: String,
+    pub case_insensitive: bool,
+    /// Worker threads. Defaults to the available parallelism.
+    pub threads: usize,
+    /// Time between progress callbacks
+    pub progress_interval: Duration,
+}
+
+impl Default for VanityGrinder {
+    fn default() -> Self {
+        Self {
+            prefix: String::new(),
+            suffix: String::new(),
+            case_insensitive: false,
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+            progress_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl VanityGrinder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    pub fn with_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = suffix.into();
+        self
+    }
+
+    pub fn with_case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    pub fn with_progress_interval(mut self, progress_interval: Duration) -> Self {
+        self.progress_interval = progress_interval;
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), VanityError> {
+        if self.prefix.is_empty() && self.suffix.is_empty() {
+            return Err(VanityError::InvalidPattern(
+                "prefix and suffix are both empty".to_string(),
+            ));
+        }
+        for c in self.prefix.chars().chain(self.suffix.chars()) {
+            if self.char_weight(c) == 0 {
+                return Err(VanityError::InvalidPattern(format!(
+                    "'{}' is not a base58 character",
+                    c
+                )));
+            }
+        }
+        let len = self.prefix.len() + self.suffix.len();
+        if len > MAX_ADDRESS_LEN {
+            return Err(VanityError::InvalidPattern(format!(
+                "{} characters, addresses have at most {}",
+                len, MAX_ADDRESS_LEN
+            )));
+        }
+        Ok(())
+    }
+
+    /// Average number of keys needed for a match. Assumes every address
+    /// character is uniformly distributed, which slightly underestimates
+    /// prefixes.
+    pub fn expected_attempts(&self) -> f64 {
+        let alphabet_len = BASE58_ALPHABET.len() as f64;
+        self.prefix
+            .chars()
+            .chain(self.suffix.chars())
+            .map(|c| alphabet_len / self.char_weight(c).max(1) as f64)
+            .product()
+    }
+
+    /// Expects a validated pattern
+    fn is_match(&self, address: &str) -> bool {
+        if address.len() < self.prefix.len() + self.suffix.len() {
+            return false;
+        }
+        let (head, tail) = (
+            &address[..self.prefix.len()],
+            &address[address.len() - self.suffix.len()..],
+        );
+        if self.case_insensitive {
+            head.eq_ignore_ascii_case(&self.prefix) && tail.eq_ignore_ascii_case(&self.suffix)
+        } else {
+            head == self.prefix && tail == self.suffix
+        }
+    }
+
+    /// Grinds until a match is found or `cancel` is triggered, calling
+    /// `progress` on the calling thread every `progress_interval`
+    pub fn grind(
+        &self,
+        cancel: &CancelToken,
+        mut progress: impl FnMut(&VanityProgress),
+    ) -> Result<Keypair, VanityError> {
+        self.validate()?;
+        let expected_attempts = self.expected_attempts();
+        let attempts = AtomicU64::new(0);
+        let done = AtomicBool::new(false);
+        let (sender, receiver) = mpsc::channel();
+        let start = Instant::now();
+        let poll_interval = self.progress_interval.min(CANCEL_POLL_INTERVAL);
+        let mut last_progress = start;
+
+        thread::scope(|scope| {
+            for _ in 0..self.threads.max(1) {
+                let sender = sender.clone();
+                let (attempts, done) = (&attempts, &done);
+                scope.spawn(move || {
+                    while !done.load(Ordering::Relaxed) && !cancel.is_cancelled() {
+                        for _ in 0..BATCH_SIZE {
+                            let keypair = Keypair::new();
+                            if self.is_match(&keypair.pubkey().to_string()) {
+                                done.store(true, Ordering::Relaxed);
+                                let _ = sender.send(keypair);
+                                break;
+                            }
+                        }
+                        attempts.fetch_add(BATCH_SIZE, Ordering::Relaxed);
+                    }
+                });
+            }
+            drop(sender);
+
+            let res = loop {
+                match receiver.recv_timeout(poll_interval) {
+                    Ok(keypair) => break Ok(keypair),
+                    Err(mpsc::RecvTimeoutError::Timeout) if !cancel.is_cancelled() => {
+                        if last_progress.elapsed() < self.progress_interval {
+                            continue;
+                        }
+                        last_progress = Instant::now();
+                        let attempts = attempts.load(Ordering::Relaxed);
+                        let elapsed = start.elapsed();
+                        let rate = attempts as f64 / elapsed.as_secs_f64();
+                        progress(&VanityProgress {
+                            attempts,
+                            elapsed,
+                            rate,
+                            expected_attempts,
+                            eta: eta(expected_attempts, rate),
+                        });
+                    }
+                    Err(_) => break Err(VanityError::Cancelled),
+                }
+            };
+            done.store(true, Ordering::Relaxed);
+            res
+        })
+    }
+
+    /// Number of base58 characters `c` matches
+    fn char_weight(&self, c: char) -> usize {
+        BASE58_ALPHABET
+            .chars()
+            .filter(|a| {
+                if self.case_insensitive {
+                    a.eq_ignore_ascii_case(&c)
+                } else {
+                    *a == c
+                }
+            })
+            .count()
+    }
+}
+
+/// `None` while the rate is unknown or when the wait does not fit a
+/// `Duration`
+fn eta(expected_attempts: f64, rate: f64) -> Option<Duration> {
+    Duration::try_from_secs_f64(expected_attempts / rate).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_patterns_longer_than_an_address() {
+        let grinder = VanityGrinder::new()
+            .with_prefix("a".repeat(40))
+            .with_suffix("pump");
+        assert!(grinder.validate().is_ok());
+
+        let grinder = grinder.with_suffix("pumps");
+        assert!(matches!(
+            grinder.grind(&CancelToken::new(), |_| {}),
+            Err(VanityError::InvalidPattern(_))
+        ));
+    }
+
+    #[test]
+    fn eta_is_none_when_unknown_or_out_of_range() {
+        assert_eq!(eta(100.0, 50.0), Some(Duration::from_secs(2)));
+        assert_eq!(eta(100.0, 0.0), None);
+        assert_eq!(eta(0.0, 0.0), None);
+        let expected_attempts = VanityGrinder::new()
+            .with_prefix("a".repeat(20))
+            .expected_attempts();
+        assert_eq!(eta(expected_attempts, 1.0), None);
+    }
+
+    #[test]
+    fn grinds_a_matching_keypair() {
+        let keypair = VanityGrinder::new()
+            .with_prefix("a")
+            .with_threads(2)
+            .grind(&CancelToken::new(), |_| {})
+            .unwrap();
+        assert!(keypair.pubkey().to_string().starts_with('a'));
+    }
+
+    #[test]
+    fn notices_cancel_before_the_progress_interval() {
+        let cancel = CancelToken::new();
+        let canceller = {
+            let cancel = cancel.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                cancel.cancel();
+            })
+        };
+
+        let start = Instant::now();
+        let res = VanityGrinder::new()
+            .with_prefix("a".repeat(20))
+            .with_threads(1)
+            .with_progress_interval(Duration::from_secs(60))
+            .grind(&cancel, |_| panic!("progress before the interval"));
+        canceller.join().unwrap();
+
+        assert_eq!(res.unwrap_err(), VanityError::Cancelled);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}